@@ -0,0 +1,269 @@
+//! Runs a simulation without opening a window, writing snapshots and diagnostics to disk
+//!
+//! ```text
+//! n-body-cli <initial-conditions> [options]
+//! ```
+#![allow(clippy::needless_return)]
+
+use anyhow::{anyhow, Context, Result};
+use n_body::export::TrajectoryWriter;
+use n_body::initial_conditions;
+use n_body::particle::Particle;
+use n_body::physics::diagnostics::ConservedQuantities;
+use n_body::scenario::ScenarioConstants;
+use n_body::simulation::{Settings, Simulation};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Usage: n-body-cli <initial-conditions> [options]
+
+The initial conditions are either a .ron or .json scenario (as loaded by the app), or a text file
+with one particle per line: x y vx vy radius density
+
+Constants given on the command line override the scenario's
+
+Options:
+  --steps <N>            Number of steps to run [default: 1000]
+  --dt <DT>              Timestep [default: 0.008333]
+  --integrator <NAME>    euler, verlet, rk4 or yoshida [default: euler]
+  --solver <NAME>        direct or barnes-hut [default: direct]
+  --collisions <NAME>    bounce or merge [default: bounce]
+  --g <G>                Gravitational constant
+  --softening <EPS>      Plummer softening length
+  --theta <THETA>        Barnes-Hut opening angle
+  --restitution <R>      Coefficient of restitution
+  --snapshot-every <N>   Steps between particle snapshots, 0 to disable [default: 100]
+  --diagnostics-every <N>
+                         Steps between diagnostics rows [default: 10]
+  --output <DIR>         Directory to write results to [default: output]
+  --trajectory <FILE>    Also write every particle's state to a .csv, .npy or .npz file
+                         (relative to the output directory)
+  --trajectory-every <N>
+                         Steps between trajectory rows [default: 10]
+  -h, --help             Print this message";
+
+/// Options parsed from the command line
+struct Options {
+    input: PathBuf,
+    steps: usize,
+    dt: f32,
+    settings: Settings,
+    /// Constants set on the command line, applied over the scenario's
+    constants: ScenarioConstants,
+    snapshot_every: usize,
+    diagnostics_every: usize,
+    output: PathBuf,
+    trajectory: Option<PathBuf>,
+    trajectory_every: usize,
+}
+
+impl Options {
+    /// Parses the options from the command line arguments (without the program name)
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut input = None;
+        let mut options = Self {
+            input: PathBuf::new(),
+            steps: 1000,
+            dt: 1.0 / 120.0,
+            settings: Settings::new(),
+            constants: ScenarioConstants::default(),
+            snapshot_every: 100,
+            diagnostics_every: 10,
+            output: PathBuf::from("output"),
+            trajectory: None,
+            trajectory_every: 10,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if input.is_some() {
+                    return Err(anyhow!("unexpected argument '{}'", arg));
+                }
+                input = Some(PathBuf::from(arg));
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("missing value for '{}'", arg))?;
+            let constants = &mut options.constants;
+            match arg.as_str() {
+                "--steps" => options.steps = parse_value(arg, value)?,
+                "--dt" => options.dt = parse_value(arg, value)?,
+                "--integrator" => options.settings.integrator = parse_value(arg, value)?,
+                "--solver" => options.settings.force_solver = parse_value(arg, value)?,
+                "--collisions" => options.settings.collision_mode = parse_value(arg, value)?,
+                "--g" => constants.g = Some(parse_value(arg, value)?),
+                "--softening" => constants.softening = Some(parse_value(arg, value)?),
+                "--theta" => constants.theta = Some(parse_value(arg, value)?),
+                "--restitution" => constants.restitution = Some(parse_value(arg, value)?),
+                "--snapshot-every" => options.snapshot_every = parse_value(arg, value)?,
+                "--diagnostics-every" => options.diagnostics_every = parse_value(arg, value)?,
+                "--output" => options.output = PathBuf::from(value),
+                "--trajectory" => options.trajectory = Some(PathBuf::from(value)),
+                "--trajectory-every" => options.trajectory_every = parse_value(arg, value)?,
+                _ => return Err(anyhow!("unknown option '{}'", arg)),
+            }
+        }
+
+        options.input = input.ok_or_else(|| anyhow!("missing initial conditions file"))?;
+        if !(options.dt.is_finite() && options.dt > 0.0) {
+            return Err(anyhow!("--dt must be positive"));
+        }
+        return Ok(options);
+    }
+}
+
+fn parse_value<T>(arg: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    return value
+        .parse::<T>()
+        .map_err(|e| anyhow!("invalid value '{}' for '{}': {}", value, arg, e));
+}
+
+/// Writes the state of every particle to a CSV file
+fn write_snapshot(path: &Path, ids: &[u64], particles: &[Particle]) -> Result<()> {
+    let mut file = BufWriter::new(
+        File::create(path).with_context(|| format!("could not create '{}'", path.display()))?,
+    );
+    writeln!(file, "id,x,y,vx,vy,ax,ay,mass,radius,density")?;
+    for (id, p) in ids.iter().zip(particles) {
+        let (pos, vel, acc) = (p.position(), p.velocity(), p.acceleration());
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},{},{}",
+            id,
+            pos.x,
+            pos.y,
+            vel.x,
+            vel.y,
+            acc.x,
+            acc.y,
+            p.mass(),
+            p.radius(),
+            p.density()
+        )?;
+    }
+    file.flush()?;
+    return Ok(());
+}
+
+/// Appends one row of conserved quantities to the diagnostics file
+fn write_diagnostics(
+    file: &mut impl Write,
+    sim: &Simulation,
+    initial: &ConservedQuantities,
+) -> Result<()> {
+    let constants = &sim.settings.constants;
+    let q =
+        ConservedQuantities::measure(&sim.particles, constants.g.value, constants.softening.value);
+    writeln!(
+        file,
+        "{},{},{},{},{},{},{},{},{},{},{},{}",
+        sim.steps(),
+        sim.time(),
+        q.kinetic_energy,
+        q.potential_energy,
+        q.total_energy(),
+        q.energy_drift(initial),
+        q.momentum.x,
+        q.momentum.y,
+        q.angular_momentum,
+        q.center_of_mass.x,
+        q.center_of_mass.y,
+        sim.particles.len()
+    )?;
+    return Ok(());
+}
+
+fn run(options: Options) -> Result<()> {
+    let scenario = initial_conditions::load_scenario(&options.input)?;
+    let particles = scenario.particles()?;
+    let mut settings = options.settings;
+    scenario.constants.apply(&mut settings.constants)?;
+    options
+        .constants
+        .apply(&mut settings.constants)
+        .context("invalid constant on the command line")?;
+    let snapshots = options.output.join("snapshots");
+    if options.snapshot_every > 0 {
+        fs::create_dir_all(&snapshots)
+            .with_context(|| format!("could not create '{}'", snapshots.display()))?;
+    } else {
+        fs::create_dir_all(&options.output)
+            .with_context(|| format!("could not create '{}'", options.output.display()))?;
+    }
+
+    let diagnostics_path = options.output.join("diagnostics.csv");
+    let mut diagnostics = BufWriter::new(
+        File::create(&diagnostics_path)
+            .with_context(|| format!("could not create '{}'", diagnostics_path.display()))?,
+    );
+    writeln!(
+        diagnostics,
+        "step,time,kinetic,potential,total,energy_drift,momentum_x,momentum_y,angular_momentum,com_x,com_y,particles"
+    )?;
+
+    let mut trajectory = match &options.trajectory {
+        Some(path) => Some(TrajectoryWriter::create(&options.output.join(path))?),
+        None => None,
+    };
+
+    let mut sim = Simulation::new(particles, settings);
+    let constants = &sim.settings.constants;
+    let initial =
+        ConservedQuantities::measure(&sim.particles, constants.g.value, constants.softening.value);
+
+    for step in 0..=options.steps {
+        if step > 0 {
+            sim.step(options.dt);
+        }
+        if options.diagnostics_every > 0 && step % options.diagnostics_every == 0 {
+            write_diagnostics(&mut diagnostics, &sim, &initial)?;
+        }
+        if options.snapshot_every > 0 && step % options.snapshot_every == 0 {
+            write_snapshot(
+                &snapshots.join(format!("step_{:08}.csv", step)),
+                sim.ids(),
+                &sim.particles,
+            )?;
+        }
+        if let Some(trajectory) = &mut trajectory {
+            if options.trajectory_every > 0 && step % options.trajectory_every == 0 {
+                trajectory.write(sim.time(), sim.ids(), &sim.particles)?;
+            }
+        }
+    }
+    diagnostics.flush()?;
+    if let Some(trajectory) = &mut trajectory {
+        trajectory.finish()?;
+    }
+
+    println!(
+        "Ran {} steps ({} particles remaining), results written to '{}'",
+        sim.steps(),
+        sim.particles.len(),
+        options.output.display()
+    );
+    return Ok(());
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    let result = Options::parse(&args).and_then(run);
+    if let Err(e) = result {
+        eprintln!("error: {:#}", e);
+        exit(1);
+    }
+}
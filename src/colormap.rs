@@ -0,0 +1,234 @@
+use crate::particle::Particle;
+use crate::physics::from_name;
+use std::str::FromStr;
+
+/// Continuous colormaps, sampled at evenly spaced stops and interpolated linearly between them
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    Inferno,
+    Plasma,
+    Greys,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 5] = [
+        Colormap::Viridis,
+        Colormap::Magma,
+        Colormap::Inferno,
+        Colormap::Plasma,
+        Colormap::Greys,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Inferno => "Inferno",
+            Colormap::Plasma => "Plasma",
+            Colormap::Greys => "Greys",
+        }
+    }
+
+    /// Short name used in files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Magma => "magma",
+            Colormap::Inferno => "inferno",
+            Colormap::Plasma => "plasma",
+            Colormap::Greys => "greys",
+        }
+    }
+
+    /// The sRGB stops of the colormap, from matplotlib
+    fn stops(&self) -> &'static [u32] {
+        match self {
+            Colormap::Viridis => &[
+                0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30,
+                0xfde725,
+            ],
+            Colormap::Magma => &[
+                0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287,
+                0xfcfdbf,
+            ],
+            Colormap::Inferno => &[
+                0x000004, 0x1f0c48, 0x550f6d, 0x88226a, 0xba3655, 0xe35933, 0xf98c0a, 0xf9c932,
+                0xfcffa4,
+            ],
+            Colormap::Plasma => &[
+                0x0d0887, 0x4c02a1, 0x7e03a8, 0xa92395, 0xcc4778, 0xe56b5d, 0xf89441, 0xfdc328,
+                0xf0f921,
+            ],
+            Colormap::Greys => &[0x000000, 0xffffff],
+        }
+    }
+
+    /// Samples the colormap
+    ///
+    /// ### Arguments
+    /// - `t` Position along the colormap, clamped to 0..=1 (`NaN` is treated as 0)
+    ///
+    /// ### Returns
+    /// `[f32; 3]` The sRGB colour, each component from 0 to 1
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let stops = self.stops();
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let x = t * (stops.len() - 1) as f32;
+        let i = (x.floor() as usize).min(stops.len() - 2);
+        let frac = x - i as f32;
+        let (a, b) = (rgb(stops[i]), rgb(stops[i + 1]));
+        return [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * frac);
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return from_name(&Self::ALL, s, Self::name);
+    }
+}
+
+fn rgb(hex: u32) -> [f32; 3] {
+    return [16, 8, 0].map(|shift| ((hex >> shift) & 0xff) as f32 / 255.0);
+}
+
+/// What decides the colour of each particle
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorMode {
+    /// The colour the particle was created with
+    #[default]
+    Assigned,
+    Speed,
+    KineticEnergy,
+    Mass,
+    Acceleration,
+    /// A fixed hue for each particle, so individual particles can be followed by eye
+    Id,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 6] = [
+        ColorMode::Assigned,
+        ColorMode::Speed,
+        ColorMode::KineticEnergy,
+        ColorMode::Mass,
+        ColorMode::Acceleration,
+        ColorMode::Id,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorMode::Assigned => "Assigned Colour",
+            ColorMode::Speed => "Speed",
+            ColorMode::KineticEnergy => "Kinetic Energy",
+            ColorMode::Mass => "Mass",
+            ColorMode::Acceleration => "Acceleration",
+            ColorMode::Id => "Particle ID",
+        }
+    }
+
+    /// Short name used in files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorMode::Assigned => "assigned",
+            ColorMode::Speed => "speed",
+            ColorMode::KineticEnergy => "kinetic-energy",
+            ColorMode::Mass => "mass",
+            ColorMode::Acceleration => "acceleration",
+            ColorMode::Id => "id",
+        }
+    }
+
+    /// Gets the quantity a continuous mode maps to colour, `None` for the other modes
+    pub fn value(&self, p: &Particle) -> Option<f32> {
+        match self {
+            ColorMode::Speed => Some(p.velocity().length()),
+            ColorMode::KineticEnergy => Some(0.5 * p.mass() * p.velocity().length_squared()),
+            ColorMode::Mass => Some(p.mass()),
+            ColorMode::Acceleration => Some(p.acceleration().length()),
+            ColorMode::Assigned | ColorMode::Id => None,
+        }
+    }
+
+    /// Whether the mode maps a quantity to a colormap
+    pub fn is_continuous(&self) -> bool {
+        return !matches!(self, ColorMode::Assigned | ColorMode::Id);
+    }
+
+    /// Whether the quantity spans so many orders of magnitude that it is mapped logarithmically
+    pub fn logarithmic(&self) -> bool {
+        return matches!(self, ColorMode::KineticEnergy | ColorMode::Mass);
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return from_name(&Self::ALL, s, Self::name);
+    }
+}
+
+/// The range of a continuous colour mode's quantity over every particle
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorScale {
+    pub mode: ColorMode,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ColorScale {
+    /// Finds the range of the mode's quantity
+    ///
+    /// ### Returns
+    /// `Option<ColorScale>` The range, `None` if the mode isn't continuous or there are no particles
+    pub fn new<'a>(
+        mode: ColorMode,
+        particles: impl IntoIterator<Item = &'a Particle>,
+    ) -> Option<Self> {
+        let (min, max) = particles
+            .into_iter()
+            .filter_map(|p| mode.value(p))
+            .fold((f32::MAX, f32::MIN), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+        if min > max {
+            return None;
+        }
+        return Some(Self { mode, min, max });
+    }
+
+    /// Gets where a particle lies in the range, 0 at the minimum and 1 at the maximum
+    ///
+    /// Every particle is at 0 when they all have the same value
+    pub fn position(&self, p: &Particle) -> f32 {
+        let Some(value) = self.mode.value(p) else {
+            return 0.0;
+        };
+        let scale = |v: f32| {
+            if self.mode.logarithmic() {
+                v.max(f32::MIN_POSITIVE).log10()
+            } else {
+                v
+            }
+        };
+        let (min, max) = (scale(self.min), scale(self.max));
+        if max - min <= f32::EPSILON * max.abs() {
+            return 0.0;
+        }
+        return ((scale(value) - min) / (max - min)).clamp(0.0, 1.0);
+    }
+}
+
+/// Gets a stable, well spread hue for an ID by stepping around the colour wheel by the golden angle
+///
+/// ### Returns
+/// `f32` The hue in degrees
+pub fn hue(id: u64) -> f32 {
+    const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+    return (id as f64 * GOLDEN_RATIO_CONJUGATE).fract() as f32 * 360.0;
+}
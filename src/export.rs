@@ -0,0 +1,287 @@
+use crate::particle::Particle;
+use anyhow::{anyhow, Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// The columns of an exported trajectory, in order
+pub const COLUMNS: [&str; 8] = ["id", "t", "x", "y", "vx", "vy", "mass", "radius"];
+
+/// Length of a streamed `.npy` header, long enough for any row count
+const NPY_HEADER_LEN: usize = 128;
+
+/// The file formats trajectories can be exported in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    /// One row per particle per record, with a header row
+    Csv,
+    /// A single `(rows, 8)` array of `f64`, with the columns in `COLUMNS` order
+    Npy,
+    /// One array per column, named after the column (`id` is `u64`, the rest `f64`)
+    Npz,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Npy, ExportFormat::Npz];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Npy => "NumPy (.npy)",
+            ExportFormat::Npz => "NumPy (.npz)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Npy => "npy",
+            ExportFormat::Npz => "npz",
+        }
+    }
+
+    /// Chooses the format from a file's extension (`.csv`, `.npy` or `.npz`)
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str());
+        return Self::ALL
+            .into_iter()
+            .find(|f| Some(f.extension()) == extension)
+            .ok_or_else(|| anyhow!("expected a .csv, .npy or .npz file"));
+    }
+}
+
+/// Where the rows go until the export is finished
+enum Sink {
+    Csv(BufWriter<File>),
+    /// Rows are streamed after a placeholder header, which is rewritten with the row count
+    Npy(BufWriter<File>),
+    /// A zip entry has to be complete before the next one starts, so each column is streamed to
+    /// a temporary file next to the archive (in `COLUMNS` order) and copied into it when finished
+    Npz(File, Vec<Column>),
+}
+
+/// A column of an `.npz` archive being recorded
+struct Column {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+/// Writes the state of every particle at chosen times to a file, for analysis outside the app
+///
+/// The writer must be finished for `.npy` and `.npz` files to be valid. Dropping it finishes it,
+/// but ignores any error, so prefer calling `finish`
+pub struct TrajectoryWriter {
+    format: ExportFormat,
+    sink: Option<Sink>,
+    rows: u64,
+}
+
+impl TrajectoryWriter {
+    /// Creates the file, the format is chosen by its extension (`.csv`, `.npy` or `.npz`)
+    ///
+    /// ### Arguments
+    /// - `path` The file to write, replaced if it exists
+    pub fn create(path: &Path) -> Result<Self> {
+        let format = ExportFormat::from_path(path)?;
+        let file =
+            File::create(path).with_context(|| format!("could not create '{}'", path.display()))?;
+        let sink = match format {
+            ExportFormat::Csv => {
+                let mut file = BufWriter::new(file);
+                writeln!(file, "{}", COLUMNS.join(","))?;
+                Sink::Csv(file)
+            }
+            ExportFormat::Npy => {
+                let mut file = BufWriter::new(file);
+                file.write_all(&npy_header("<f8", &table_shape(0), Some(NPY_HEADER_LEN)))?;
+                Sink::Npy(file)
+            }
+            ExportFormat::Npz => {
+                let mut columns = Vec::new();
+                for column in COLUMNS {
+                    let path = column_path(path, column);
+                    let file = OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .create(true)
+                        .truncate(true)
+                        .open(&path)
+                        .with_context(|| format!("could not create '{}'", path.display()))?;
+                    columns.push(Column {
+                        path,
+                        file: BufWriter::new(file),
+                    });
+                }
+                Sink::Npz(file, columns)
+            }
+        };
+        return Ok(Self {
+            format,
+            sink: Some(sink),
+            rows: 0,
+        });
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        return self.format;
+    }
+
+    /// Gets the number of rows written so far
+    pub fn rows(&self) -> u64 {
+        return self.rows;
+    }
+
+    /// Writes a row for every particle
+    ///
+    /// ### Arguments
+    /// - `time` The simulated time
+    /// - `ids` The ID of each particle, in the same order as `particles`
+    /// - `particles` The particles
+    pub fn write(&mut self, time: f64, ids: &[u64], particles: &[Particle]) -> Result<()> {
+        if ids.len() != particles.len() {
+            return Err(anyhow!(
+                "{} IDs given for {} particles",
+                ids.len(),
+                particles.len()
+            ));
+        }
+        let sink = self
+            .sink
+            .as_mut()
+            .ok_or_else(|| anyhow!("the export has already been finished"))?;
+        for (id, p) in ids.iter().zip(particles) {
+            let (pos, vel) = (p.position(), p.velocity());
+            let values = [
+                time,
+                pos.x as f64,
+                pos.y as f64,
+                vel.x as f64,
+                vel.y as f64,
+                p.mass() as f64,
+                p.radius() as f64,
+            ];
+            match sink {
+                Sink::Csv(file) => {
+                    write!(file, "{}", id)?;
+                    for value in values {
+                        write!(file, ",{}", value)?;
+                    }
+                    writeln!(file)?;
+                }
+                Sink::Npy(file) => {
+                    // IDs are exact as f64 up to 2^53
+                    file.write_all(&(*id as f64).to_le_bytes())?;
+                    for value in values {
+                        file.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                Sink::Npz(_, columns) => {
+                    columns[0].file.write_all(&id.to_le_bytes())?;
+                    for (column, value) in columns[1..].iter_mut().zip(values) {
+                        column.file.write_all(&value.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+        self.rows += particles.len() as u64;
+        return Ok(());
+    }
+
+    /// Flushes the rows to the file and completes it, further writes are errors
+    pub fn finish(&mut self) -> Result<()> {
+        let Some(sink) = self.sink.take() else {
+            return Ok(());
+        };
+        match sink {
+            Sink::Csv(mut file) => file.flush()?,
+            Sink::Npy(file) => {
+                let mut file = file.into_inner().map_err(|e| e.into_error())?;
+                file.seek(SeekFrom::Start(0))?;
+                file.write_all(&npy_header(
+                    "<f8",
+                    &table_shape(self.rows),
+                    Some(NPY_HEADER_LEN),
+                ))?;
+                file.flush()?;
+            }
+            Sink::Npz(file, columns) => {
+                let paths: Vec<PathBuf> = columns.iter().map(|c| c.path.clone()).collect();
+                let result = write_npz(file, columns, self.rows);
+                for path in paths {
+                    let _ = std::fs::remove_file(path);
+                }
+                result?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl Drop for TrajectoryWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Copies the recorded columns into an `.npz` archive, as one `.npy` file per column
+///
+/// ### Arguments
+/// - `file` The archive
+/// - `columns` The temporary file of each column, in `COLUMNS` order
+/// - `rows` The number of values in each column
+fn write_npz(file: File, columns: Vec<Column>, rows: u64) -> Result<()> {
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(rows * 8 > u32::MAX as u64);
+    let shape = format!("({},)", rows);
+    for (name, column) in COLUMNS.iter().zip(columns) {
+        let descr = if *name == "id" { "<u8" } else { "<f8" };
+        let mut data = column.file.into_inner().map_err(|e| e.into_error())?;
+        data.seek(SeekFrom::Start(0))?;
+        zip.start_file(format!("{}.npy", name), options)?;
+        zip.write_all(&npy_header(descr, &shape, None))?;
+        std::io::copy(&mut data, &mut zip)?;
+    }
+    zip.finish()?;
+    return Ok(());
+}
+
+/// The temporary file a column of an `.npz` archive is recorded to, e.g. `run.npz.x.tmp`
+fn column_path(path: &Path, column: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", column));
+    return path.with_file_name(name);
+}
+
+/// The shape of a `.npy` table with a column per field in `COLUMNS`
+fn table_shape(rows: u64) -> String {
+    return format!("({}, {})", rows, COLUMNS.len());
+}
+
+/// Creates the header of a version 1.0 `.npy` file
+///
+/// ### Arguments
+/// - `descr` The NumPy type of the elements, e.g. `<f8`
+/// - `shape` The shape of the array as a Python tuple, e.g. `(10, 8)`
+/// - `len` The total length to pad the header to, so it can be rewritten in place. The header is
+///   padded to a multiple of 64 bytes when `None`
+fn npy_header(descr: &str, shape: &str, len: Option<usize>) -> Vec<u8> {
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // Magic string, version and header length take 10 bytes, the dictionary ends with a newline
+    let total = len.unwrap_or_else(|| (10 + dict.len() + 1).div_ceil(64) * 64);
+    while 10 + dict.len() + 1 < total {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend((dict.len() as u16).to_le_bytes());
+    header.extend(dict.as_bytes());
+    return header;
+}
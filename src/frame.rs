@@ -0,0 +1,68 @@
+use crate::particle::path::Path;
+use glam::Vec2;
+
+/// Where a reference frame was, and which way it faced, when a trail point was recorded
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct FramePoint {
+    pub origin: Vec2,
+    /// Rotation of the frame from the inertial frame, in radians
+    pub angle: f32,
+}
+
+impl FramePoint {
+    /// A frame that moves with a point without rotating
+    pub fn translating(origin: Vec2) -> Self {
+        return Self { origin, angle: 0.0 };
+    }
+
+    /// A frame centred on the centre of mass of a pair, rotating so the pair stay on its x axis
+    ///
+    /// ### Arguments
+    /// - `a` The position of the first body
+    /// - `mass_a` The mass of the first body
+    /// - `b` The position of the second body
+    /// - `mass_b` The mass of the second body
+    pub fn corotating(a: Vec2, mass_a: f32, b: Vec2, mass_b: f32) -> Self {
+        let total = mass_a + mass_b;
+        let origin = if total > 0.0 {
+            (a * mass_a + b * mass_b) / total
+        } else {
+            (a + b) / 2.0
+        };
+        let separation = b - a;
+        let angle = if separation.length_squared() > 0.0 {
+            separation.y.atan2(separation.x)
+        } else {
+            0.0
+        };
+        return Self { origin, angle };
+    }
+}
+
+/// Re-projects a trail recorded in the inertial frame into a moving frame
+///
+/// The trail and the frame's history are sampled at the same times and aligned at their newest
+/// ends, so trail points older than the frame's history are dropped. The trail is shown as it is
+/// seen from the frame, placed where the frame is now (its newest sample)
+///
+/// ### Arguments
+/// - `trail` The inertial trail, oldest first
+/// - `frames` The frame at each sample, oldest first
+///
+/// ### Returns
+/// `Vec<Vec2>` The projected trail, oldest first
+pub fn project(trail: &Path, frames: &[FramePoint]) -> Vec<Vec2> {
+    let Some(now) = frames.last() else {
+        return Vec::new();
+    };
+    let n = trail.size().min(frames.len());
+    return trail
+        .points
+        .iter()
+        .skip(trail.size() - n)
+        .zip(&frames[frames.len() - n..])
+        .map(|(p, frame)| {
+            now.origin + Vec2::from_angle(now.angle - frame.angle).rotate(*p - frame.origin)
+        })
+        .collect();
+}
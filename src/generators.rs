@@ -0,0 +1,409 @@
+use crate::particle::Particle;
+use crate::physics;
+use glam::Vec2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+/// Attempts at finding a position that doesn't overlap an existing particle before giving up
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+/// The generators that can be selected at runtime
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GeneratorKind {
+    #[default]
+    PlummerSphere,
+    ExponentialDisk,
+    KeplerianRing,
+    Binary,
+    HierarchicalTriple,
+    Grid,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 6] = [
+        GeneratorKind::PlummerSphere,
+        GeneratorKind::ExponentialDisk,
+        GeneratorKind::KeplerianRing,
+        GeneratorKind::Binary,
+        GeneratorKind::HierarchicalTriple,
+        GeneratorKind::Grid,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GeneratorKind::PlummerSphere => "Plummer Sphere",
+            GeneratorKind::ExponentialDisk => "Exponential Disk",
+            GeneratorKind::KeplerianRing => "Keplerian Ring",
+            GeneratorKind::Binary => "Binary",
+            GeneratorKind::HierarchicalTriple => "Hierarchical Triple",
+            GeneratorKind::Grid => "Grid",
+        }
+    }
+
+    /// Whether the number of particles can be chosen
+    pub fn has_count(&self) -> bool {
+        return !matches!(
+            self,
+            GeneratorKind::Binary | GeneratorKind::HierarchicalTriple
+        );
+    }
+}
+
+/// A generator and the few values that are exposed for it, everything else uses fixed defaults
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GeneratorParams {
+    pub kind: GeneratorKind,
+    /// Number of particles (ignored by the binary and triple)
+    pub count: usize,
+    /// Characteristic length: scale radius, scale length, outer radius, separation or spacing
+    pub size: f32,
+    pub seed: u64,
+}
+
+impl Default for GeneratorParams {
+    fn default() -> Self {
+        Self {
+            kind: GeneratorKind::default(),
+            count: 200,
+            size: 100.0,
+            seed: 0,
+        }
+    }
+}
+
+impl GeneratorParams {
+    /// Generates the particles
+    ///
+    /// ### Arguments
+    /// - `g` The gravitational constant the velocities are calculated for
+    /// - `softening` The softening length the velocities are calculated for (only used by
+    ///   `GeneratorKind::PlummerSphere`, the orbits of the others are far wider than it)
+    pub fn generate(&self, g: f32, softening: f32) -> Vec<Particle> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let size = self.size.max(1.0);
+        let sun = 5e6;
+        match self.kind {
+            GeneratorKind::PlummerSphere => {
+                plummer_sphere(self.count, size, 1e6, g, softening, &mut rng)
+            }
+            GeneratorKind::ExponentialDisk => {
+                exponential_disk(self.count, size, 2e5, sun, g, &mut rng)
+            }
+            GeneratorKind::KeplerianRing => {
+                keplerian_ring(self.count, size * 0.6, size, sun, g, &mut rng)
+            }
+            GeneratorKind::Binary => binary(sun, sun * 0.5, size, g),
+            GeneratorKind::HierarchicalTriple => {
+                hierarchical_triple(sun, sun * 0.5, sun * 0.2, size * 0.4, size * 2.0, g)
+            }
+            GeneratorKind::Grid => {
+                // The smallest square that fits them all, with the last row left partly empty
+                let side = (self.count as f32).sqrt().ceil() as usize;
+                let mut particles = grid(Vec2::ZERO, side, size * 0.1, 2.0, 1.0);
+                particles.truncate(self.count);
+                particles
+            }
+        }
+    }
+}
+
+/// Creates a particle with a given mass, the density is chosen to match the radius
+fn body(pos: Vec2, vel: Vec2, mass: f32, radius: f32) -> Particle {
+    let mut p = Particle::default();
+    p.set_pos(pos);
+    p.set_vel(vel);
+    // Radii are positive constants
+    p.set_radius(radius).unwrap();
+    p.set_mass_with_density(mass);
+    return p;
+}
+
+/// Samples positions until one doesn't overlap any existing particle (or the attempts run out)
+fn place(
+    particles: &[Particle],
+    radius: f32,
+    rng: &mut StdRng,
+    mut sample: impl FnMut(&mut StdRng) -> Vec2,
+) -> Vec2 {
+    let mut pos = sample(rng);
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        let overlaps = particles
+            .iter()
+            .any(|p| p.position().distance(pos) < p.radius() + radius);
+        if !overlaps {
+            break;
+        }
+        pos = sample(rng);
+    }
+    return pos;
+}
+
+/// Samples a standard normal value (Box-Muller)
+fn gaussian(rng: &mut StdRng) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen();
+    return (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos();
+}
+
+/// Shifts the particles so their centre of mass is at rest at the origin
+fn to_center_of_mass_frame(particles: &mut [Particle]) {
+    let mass: f32 = particles.iter().map(|p| p.mass()).sum();
+    if mass <= 0.0 {
+        return;
+    }
+    let com = physics::center_of_mass(particles).unwrap_or(Vec2::ZERO);
+    let momentum: Vec2 = particles.iter().map(|p| p.velocity() * p.mass()).sum();
+    for p in particles.iter_mut() {
+        p.set_pos(p.position() - com);
+        p.set_vel(p.velocity() - momentum / mass);
+    }
+}
+
+/// A Plummer sphere, viewed face on, in virial equilibrium (2K + W = 0)
+///
+/// Positions follow the projected Plummer profile `Σ(R) ∝ (1 + R²/a²)⁻²` out to 10 `a`. Velocities
+/// are isotropic with the Plummer dispersion profile and then scaled to satisfy the virial theorem
+/// exactly for the sampled positions, under the softened force the sphere will be simulated with
+///
+/// ### Arguments
+/// - `n` The number of particles
+/// - `scale_radius` The Plummer radius (a)
+/// - `total_mass` The combined mass of every particle
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length of the force
+/// - `rng` The random number generator
+pub fn plummer_sphere(
+    n: usize,
+    scale_radius: f32,
+    total_mass: f32,
+    g: f32,
+    softening: f32,
+    rng: &mut StdRng,
+) -> Vec<Particle> {
+    let mass = total_mass / n.max(1) as f32;
+    let radius = 1.5;
+    let mut particles: Vec<Particle> = Vec::with_capacity(n);
+    for _ in 0..n {
+        let pos = place(&particles, radius, rng, |rng| {
+            // Inverse of the cumulative mass M(<R) / M = R² / (R² + a²), truncated at 10 a
+            let u: f32 = rng.gen_range(0.0..0.99);
+            let r = scale_radius * (u / (1.0 - u)).sqrt();
+            Vec2::from_angle(rng.gen_range(0.0..TAU)) * r
+        });
+        let sigma = (1.0 + pos.length_squared() / (scale_radius * scale_radius)).powf(-0.25);
+        let vel = Vec2::new(gaussian(rng), gaussian(rng)) * sigma;
+        particles.push(body(pos, vel, mass, radius));
+    }
+    to_center_of_mass_frame(&mut particles);
+
+    let kinetic: f32 = particles
+        .iter()
+        .map(|p| 0.5 * p.mass() * p.velocity().length_squared())
+        .sum();
+    let virial = virial(&particles, g, softening);
+    if kinetic > 0.0 {
+        let scale = (-virial / (2.0 * kinetic)).sqrt();
+        for p in particles.iter_mut() {
+            p.set_vel(p.velocity() * scale);
+        }
+    }
+    return particles;
+}
+
+/// Gets the virial of the gravitational forces, `Σ r·F` over every particle
+///
+/// Equal to the potential energy without softening, smaller in magnitude with it. A system is in
+/// virial equilibrium when twice its kinetic energy cancels this out
+///
+/// ### Arguments
+/// - `particles` The particles
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length
+pub fn virial(particles: &[Particle], g: f32, softening: f32) -> f32 {
+    let mut virial = 0.0;
+    for (i, a) in particles.iter().enumerate() {
+        for b in &particles[i + 1..] {
+            let acc = physics::gravitational_acceleration(
+                a.position(),
+                b.position(),
+                b.mass(),
+                g,
+                softening,
+            );
+            virial += a.mass() * (a.position() - b.position()).dot(acc);
+        }
+    }
+    return virial;
+}
+
+/// A rotating exponential disk around a central mass
+///
+/// Surface density follows `Σ(R) ∝ exp(-R / R_d)` out to 5 `R_d`. Each particle is given the
+/// circular velocity for the central mass plus the disk mass inside its radius, with a 5% random
+/// dispersion. The central mass is the first particle
+///
+/// ### Arguments
+/// - `n` The number of disk particles
+/// - `scale_length` The scale length (R_d)
+/// - `disk_mass` The combined mass of the disk particles
+/// - `central_mass` The mass at the centre
+/// - `g` The gravitational constant
+/// - `rng` The random number generator
+pub fn exponential_disk(
+    n: usize,
+    scale_length: f32,
+    disk_mass: f32,
+    central_mass: f32,
+    g: f32,
+    rng: &mut StdRng,
+) -> Vec<Particle> {
+    let mass = disk_mass / n.max(1) as f32;
+    let radius = 1.5;
+    let center = body(Vec2::ZERO, Vec2::ZERO, central_mass, 5.0);
+    let min_r = center.radius() * 3.0;
+    let mut particles = vec![center];
+    for _ in 0..n {
+        let pos = place(&particles, radius, rng, |rng| {
+            // The radius of an exponential disk is gamma distributed (shape 2)
+            let r = loop {
+                let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+                let u2: f32 = rng.gen_range(f32::EPSILON..1.0);
+                let r = -scale_length * (u1 * u2).ln();
+                if r <= 5.0 * scale_length {
+                    break r.max(min_r);
+                }
+            };
+            Vec2::from_angle(rng.gen_range(0.0..TAU)) * r
+        });
+        let r = pos.length();
+        let x = r / scale_length;
+        let enclosed = central_mass + disk_mass * (1.0 - (1.0 + x) * (-x).exp());
+        let speed = (g * enclosed / r).sqrt();
+        let tangent = pos.perp() / r;
+        let vel = tangent * speed + Vec2::new(gaussian(rng), gaussian(rng)) * speed * 0.05;
+        particles.push(body(pos, vel, mass, radius));
+    }
+    to_center_of_mass_frame(&mut particles);
+    return particles;
+}
+
+/// A ring of light debris on circular orbits around a central mass
+///
+/// The debris is spread uniformly over the area of the ring and is light enough not to disturb
+/// the orbits. The central mass is the first particle
+///
+/// ### Arguments
+/// - `n` The number of debris particles
+/// - `inner` The inner radius of the ring
+/// - `outer` The outer radius of the ring
+/// - `central_mass` The mass at the centre
+/// - `g` The gravitational constant
+/// - `rng` The random number generator
+pub fn keplerian_ring(
+    n: usize,
+    inner: f32,
+    outer: f32,
+    central_mass: f32,
+    g: f32,
+    rng: &mut StdRng,
+) -> Vec<Particle> {
+    let radius = 1.0;
+    let mass = central_mass * 1e-7;
+    let center = body(Vec2::ZERO, Vec2::ZERO, central_mass, 5.0);
+    let (inner, outer) = (inner.min(outer), inner.max(outer));
+    let mut particles = vec![center];
+    for _ in 0..n {
+        let pos = place(&particles, radius, rng, |rng| {
+            let r = rng
+                .gen_range(inner * inner..=outer * outer)
+                .sqrt()
+                .max(f32::EPSILON);
+            Vec2::from_angle(rng.gen_range(0.0..TAU)) * r
+        });
+        let vel = physics::orbital_velocity(pos, Vec2::ZERO, central_mass, g);
+        particles.push(body(pos, vel, mass, radius));
+    }
+    return particles;
+}
+
+/// Two bodies on a circular orbit about their centre of mass, which is at rest at the origin
+///
+/// ### Arguments
+/// - `m1` The mass of the first body
+/// - `m2` The mass of the second body
+/// - `separation` The distance between the bodies
+/// - `g` The gravitational constant
+pub fn binary(m1: f32, m2: f32, separation: f32, g: f32) -> Vec<Particle> {
+    let total = m1 + m2;
+    let speed = (g * total / separation).sqrt();
+    let radius = |m: f32| (m / 5e6).cbrt() * 5.0;
+    return vec![
+        body(
+            Vec2::new(-separation * m2 / total, 0.0),
+            Vec2::new(0.0, -speed * m2 / total),
+            m1,
+            radius(m1),
+        ),
+        body(
+            Vec2::new(separation * m1 / total, 0.0),
+            Vec2::new(0.0, speed * m1 / total),
+            m2,
+            radius(m2),
+        ),
+    ];
+}
+
+/// A close binary orbited by a third body, with every orbit circular
+///
+/// The system's centre of mass is at rest at the origin
+///
+/// ### Arguments
+/// - `m1` `m2` The masses of the inner binary
+/// - `m3` The mass of the outer body
+/// - `inner` The separation of the inner binary
+/// - `outer` The distance from the outer body to the inner binary's centre of mass
+/// - `g` The gravitational constant
+pub fn hierarchical_triple(
+    m1: f32,
+    m2: f32,
+    m3: f32,
+    inner: f32,
+    outer: f32,
+    g: f32,
+) -> Vec<Particle> {
+    let mut particles = binary(m1 + m2, m3, outer, g);
+    let (center, third) = (particles[0].clone(), particles.remove(1));
+    let mut particles = binary(m1, m2, inner, g);
+    for p in particles.iter_mut() {
+        p.set_pos(p.position() + center.position());
+        p.set_vel(p.velocity() + center.velocity());
+    }
+    particles.push(third);
+    return particles;
+}
+
+/// A square grid of particles at rest
+///
+/// ### Arguments
+/// - `center` The centre of the grid
+/// - `n` The number of particles along each side
+/// - `spacing` The gap between neighbouring particles' edges
+/// - `radius` The radius of each particle
+/// - `density` The density of each particle
+pub fn grid(center: Vec2, n: usize, spacing: f32, radius: f32, density: f32) -> Vec<Particle> {
+    let step = 2.0 * radius + spacing;
+    let start = center - Vec2::splat(step * (n.max(1) - 1) as f32 / 2.0);
+    let mut particles = Vec::with_capacity(n * n);
+    for i in 0..n {
+        for j in 0..n {
+            let mut p = Particle::default();
+            p.set_pos(start + Vec2::new(j as f32, i as f32) * step);
+            // Negative values are clamped so the setters can't fail
+            p.set_radius(radius.max(0.0)).unwrap();
+            p.set_density(density.max(0.0)).unwrap();
+            particles.push(p);
+        }
+    }
+    return particles;
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Most samples kept, however long the window is
+const MAX_SAMPLES: usize = 100_000;
+
+/// The state of the simulation at one moment
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Sample {
+    /// Simulated time, in seconds
+    pub time: f64,
+    pub total_energy: f32,
+    pub kinetic_energy: f32,
+    pub potential_energy: f32,
+    /// Magnitude of the total momentum
+    pub momentum: f32,
+    pub particles: usize,
+    /// Real time taken by a physics step, in seconds
+    pub step_time: f32,
+}
+
+/// A quantity recorded in every sample
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Series {
+    TotalEnergy,
+    KineticEnergy,
+    PotentialEnergy,
+    Momentum,
+    Particles,
+    StepTime,
+}
+
+impl Series {
+    pub const ALL: [Series; 6] = [
+        Series::TotalEnergy,
+        Series::KineticEnergy,
+        Series::PotentialEnergy,
+        Series::Momentum,
+        Series::Particles,
+        Series::StepTime,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Series::TotalEnergy => "Total Energy",
+            Series::KineticEnergy => "Kinetic Energy",
+            Series::PotentialEnergy => "Potential Energy",
+            Series::Momentum => "Momentum",
+            Series::Particles => "Particles",
+            Series::StepTime => "Step Time (ms)",
+        }
+    }
+
+    /// The column name used in CSV files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Series::TotalEnergy => "total_energy",
+            Series::KineticEnergy => "kinetic_energy",
+            Series::PotentialEnergy => "potential_energy",
+            Series::Momentum => "momentum",
+            Series::Particles => "particles",
+            Series::StepTime => "step_time_ms",
+        }
+    }
+
+    /// Gets the quantity from a sample, step times are in milliseconds
+    pub fn value(&self, sample: &Sample) -> f64 {
+        match self {
+            Series::TotalEnergy => sample.total_energy as f64,
+            Series::KineticEnergy => sample.kinetic_energy as f64,
+            Series::PotentialEnergy => sample.potential_energy as f64,
+            Series::Momentum => sample.momentum as f64,
+            Series::Particles => sample.particles as f64,
+            Series::StepTime => sample.step_time as f64 * 1000.0,
+        }
+    }
+}
+
+/// The samples taken over the last `window` seconds of simulated time
+pub struct History {
+    samples: VecDeque<Sample>,
+    /// How far back samples are kept, in simulated seconds
+    pub window: f64,
+}
+
+impl History {
+    pub fn new(window: f64) -> Self {
+        return Self {
+            samples: VecDeque::new(),
+            window,
+        };
+    }
+
+    /// Adds a sample, dropping any that are now outside the window
+    ///
+    /// A sample from before the newest one means the simulation was restarted, so the old
+    /// samples are cleared
+    pub fn push(&mut self, sample: Sample) {
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| sample.time < last.time)
+        {
+            self.samples.clear();
+        }
+        self.samples.push_back(sample);
+        let start = sample.time - self.window;
+        while self.samples.len() > MAX_SAMPLES
+            || self.samples.front().is_some_and(|first| first.time < start)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Gets the samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+        return self.samples.iter();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.samples.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.samples.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Gets a quantity against simulated time, ready to plot
+    pub fn series(&self, series: Series) -> Vec<[f64; 2]> {
+        return self
+            .samples
+            .iter()
+            .map(|s| [s.time, series.value(s)])
+            .collect();
+    }
+
+    /// Formats the samples as CSV, with a header row and one row per sample
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time");
+        for series in Series::ALL {
+            csv.push(',');
+            csv.push_str(series.name());
+        }
+        csv.push('\n');
+        for sample in &self.samples {
+            // Writing to a String can't fail
+            let _ = write!(csv, "{}", sample.time);
+            for series in Series::ALL {
+                let _ = write!(csv, ",{}", series.value(sample));
+            }
+            csv.push('\n');
+        }
+        return csv;
+    }
+
+    /// Writes the samples to a CSV file
+    pub fn save_csv(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_csv())
+            .with_context(|| format!("could not write '{}'", path.display()))?;
+        return Ok(());
+    }
+}
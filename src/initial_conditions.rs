@@ -0,0 +1,77 @@
+use crate::particle::Particle;
+use crate::scenario::Scenario;
+use anyhow::{anyhow, Context, Result};
+use glam::Vec2;
+use std::path::Path;
+
+/// Parses initial conditions from a plain text table
+///
+/// Each non-empty line that isn't a comment (`#`) describes one particle as 6 whitespace separated
+/// numbers: `x y vx vy radius density`
+///
+/// ### Arguments
+/// - `text` The table to parse
+///
+/// ### Returns
+/// `Result<Vec<Particle>>` The particles, or an error naming the line that could not be parsed
+pub fn parse(text: &str) -> Result<Vec<Particle>> {
+    let mut particles = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let p = parse_particle(line).with_context(|| format!("line {}: '{}'", i + 1, line))?;
+        particles.push(p);
+    }
+    return Ok(particles);
+}
+
+fn parse_particle(line: &str) -> Result<Particle> {
+    let values = line
+        .split_whitespace()
+        .map(|v| {
+            v.parse::<f32>()
+                .map_err(|_| anyhow!("'{}' is not a number", v))
+        })
+        .collect::<Result<Vec<f32>>>()?;
+    if values.len() != 6 {
+        return Err(anyhow!(
+            "expected 6 values (x y vx vy radius density), found {}",
+            values.len()
+        ));
+    }
+    let mut p = Particle::default();
+    p.set_pos(Vec2::new(values[0], values[1]));
+    p.set_vel(Vec2::new(values[2], values[3]));
+    p.set_radius(values[4])?;
+    p.set_density(values[5])?;
+    return Ok(p);
+}
+
+/// Loads initial conditions from a file, see `parse` for the format
+pub fn load(path: &Path) -> Result<Vec<Particle>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read '{}'", path.display()))?;
+    return parse(&text)
+        .with_context(|| format!("invalid initial conditions in '{}'", path.display()));
+}
+
+/// Loads a scenario (`.ron` or `.json`), or a plain text table of initial conditions (any other
+/// extension), so either can be run headless
+///
+/// ### Arguments
+/// - `path` The file to load
+///
+/// ### Returns
+/// `Result<Scenario>` The scenario, a table becomes a scenario named after the file that leaves
+/// every constant unchanged
+pub fn load_scenario(path: &Path) -> Result<Scenario> {
+    return match path.extension().and_then(|e| e.to_str()) {
+        Some("ron") | Some("json") => Scenario::load(path),
+        _ => {
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            Ok(Scenario::from_particles(name, &load(path)?))
+        }
+    };
+}
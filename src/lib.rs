@@ -1,6 +1,20 @@
+// Explicit returns are the style used throughout the crate
+#![allow(clippy::needless_return)]
+
+pub mod colormap;
 pub mod error;
+pub mod export;
+pub mod frame;
+pub mod generators;
+pub mod history;
+pub mod initial_conditions;
 pub mod particle;
 pub mod physics;
+pub mod presets;
 pub mod resources;
+pub mod scenario;
+pub mod simulation;
+pub mod snapshot;
+#[cfg(feature = "app")]
 pub mod systems;
 pub mod utils;
@@ -3,34 +3,105 @@ use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
 use bevy_prototype_lyon::prelude::*;
 use n_body::resources;
+use n_body::resources::camera::CameraControls;
+use n_body::resources::clock;
+use n_body::resources::export::{NextParticleId, TrajectoryExport};
+use n_body::resources::field::FieldOverlay;
+use n_body::resources::frame::TrailFrame;
+use n_body::resources::history::HistoryPlot;
 use n_body::resources::input;
+use n_body::resources::scenario::ScenarioFile;
+use n_body::resources::selection::Selection;
+use n_body::resources::snapshot::SnapshotFile;
+use n_body::resources::spawn::SpawnSettings;
 use n_body::systems;
 
 fn main() {
+    // Optional scenario file: n-body [scenario.ron|scenario.json]
+    let scenario = std::env::args().nth(1);
     App::new()
         .insert_resource(resources::SimulationState::new())
         .insert_resource(input::MouseState::default())
+        .insert_resource(clock::SimulationClock::new())
+        .insert_resource(CameraControls::default())
+        .insert_resource(TrailFrame::default())
+        .insert_resource(FieldOverlay::default())
+        .insert_resource(HistoryPlot::default())
+        .insert_resource(TrajectoryExport::default())
+        .insert_resource(NextParticleId::default())
+        .insert_resource(Selection::default())
+        .insert_resource(SpawnSettings::default())
+        .insert_resource(ScenarioFile::new(scenario))
+        .insert_resource(SnapshotFile::default())
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin)
         .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(systems::diagnostics::ConservationDiagnosticsPlugin)
         .add_plugins(ShapePlugin)
         .add_systems(
             Startup,
-            (systems::setup, systems::particles::spawn_initial).chain(),
+            (systems::setup, systems::path::setup, systems::field::setup),
         )
         // .add_systems(PreUpdate, systems::gui::absorb_gui_inputs.after(bevy_egui::systems::process_input_system).before(bevy_egui::EguiSet::BeginFrame))
-        .add_systems(Update, systems::gui::gui)
+        .add_systems(Update, (systems::gui::gui, systems::history::window))
         .add_systems(
             Update,
-            (systems::input::mouse_hold, systems::particles::spawn_input),
+            (
+                (
+                    systems::input::mouse_hold,
+                    systems::selection::pick,
+                    systems::particles::spawn_input,
+                )
+                    .chain(),
+                systems::input::clock_shortcuts,
+            ),
         )
         .add_systems(
             Update,
-            (systems::path::update, systems::path::render).chain(),
+            (systems::selection::inspector, systems::selection::highlight).chain(),
         )
         .add_systems(
             Update,
-            (systems::particles::update, systems::particles::render).chain(),
+            (
+                systems::camera::zoom,
+                systems::camera::pan,
+                systems::camera::follow_shortcuts,
+            ),
+        )
+        .add_systems(
+            Update,
+            (systems::path::update, systems::path::render)
+                .chain()
+                .after(systems::clock::run_physics),
+        )
+        .add_systems(
+            Update,
+            (systems::history::record, systems::history::export)
+                .chain()
+                .after(systems::diagnostics::ConservationDiagnosticsPlugin::diagnostic_system),
+        )
+        .add_systems(
+            Update,
+            (systems::export::assign_ids, systems::export::control)
+                .chain()
+                .after(systems::clock::run_physics),
+        )
+        .add_systems(
+            systems::clock::PhysicsSchedule,
+            (systems::particles::update, systems::export::record).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                systems::scenario::load,
+                systems::snapshot::save_load,
+                systems::clock::run_physics,
+                systems::camera::follow,
+                systems::particles::render,
+                systems::vectors::update,
+                systems::field::update,
+            )
+                .chain(),
         )
         .run()
 }
@@ -1,13 +1,24 @@
-use crate::particle::path::Path;
+use crate::particle::path::{self, Path};
 use crate::particle::Particle;
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
+/// The colour a particle was created with, shown when colouring by `ColorMode::Assigned`
+#[derive(Component, Clone, Copy)]
+pub struct BodyColor(pub Color);
+
+/// A particle's ID in exported trajectories, never reused and kept by the survivor of a merge
+///
+/// Given to every particle the frame after it is spawned
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParticleId(pub u64);
+
 /// Bundles Particle physics and rendering
 #[derive(Bundle)]
 pub struct ParticleBundle {
     shape_bundle: ShapeBundle,
     fill: Fill,
+    color: BodyColor,
     stroke: Stroke,
     particle: Particle,
     path: Path,
@@ -18,15 +29,13 @@ impl ParticleBundle {
     pub fn new(particle: Particle, color: Color, stroke: Option<Stroke>) -> Self {
         ParticleBundle {
             shape_bundle: ShapeBundle {
-                path: GeometryBuilder::build_as(&shapes::Circle {
-                    radius: particle.radius(),
-                    ..default()
-                }),
+                path: particle.shape(),
                 ..default()
             },
             particle,
-            path: Path::new(200),
+            path: Path::new(path::DEFAULT_CAPACITY),
             fill: Fill::color(color),
+            color: BodyColor(color),
             stroke: if let Some(stroke) = stroke {
                 stroke
             } else {
@@ -36,10 +45,31 @@ impl ParticleBundle {
     }
 }
 
+impl Particle {
+    /// Creates a ParticleBundle
+    ///
+    /// ### Arguments
+    /// - color `Color`: The color to render the particle as
+    /// - meshes `&mut ResMut<Assets<Mesh>>`: Mesh resource
+    /// - materials: `&mut ResMut<Assets<ColorMaterial>>: Materials resource
+    pub fn bundle(&self, color: Color, stroke: Option<Stroke>) -> ParticleBundle {
+        return ParticleBundle::new(self.clone(), color, stroke);
+    }
+
+    /// Creates the circle geometry the particle is rendered with
+    pub fn shape(&self) -> bevy_prototype_lyon::prelude::Path {
+        return GeometryBuilder::build_as(&shapes::Circle {
+            radius: self.radius(),
+            ..default()
+        });
+    }
+}
+
 impl Default for ParticleBundle {
     fn default() -> Self {
         Self {
             fill: Fill::color(Color::WHITE),
+            color: BodyColor(Color::WHITE),
             stroke: Stroke::new(Color::rgba(0., 0., 0., 0.), 1.0),
             ..default()
         }
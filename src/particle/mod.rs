@@ -1,20 +1,21 @@
+#[cfg(feature = "app")]
 mod bundle;
 pub mod path;
 
-use crate::particle::bundle::ParticleBundle;
+#[cfg(feature = "app")]
+pub use bundle::{BodyColor, ParticleId};
+
 use anyhow::Result;
-use bevy::prelude::*;
-use bevy_prototype_lyon::prelude::*;
+use glam::Vec2;
 
-#[derive(Component, Clone)]
+#[cfg_attr(feature = "app", derive(bevy::prelude::Component))]
+#[derive(Clone)]
 pub struct Particle {
     pos: Vec2,
     vel: Vec2,
     acc: Vec2,
     radius: f32,
     density: f32,
-    points: Vec<Vec2>,
-    max_points: usize,
 }
 
 impl Default for Particle {
@@ -25,8 +26,6 @@ impl Default for Particle {
             acc: Vec2::default(),
             density: 1.0,
             radius: 0.0,
-            points: vec![],
-            max_points: 100,
         }
     }
 }
@@ -105,23 +104,6 @@ impl Particle {
         self.radius = (mass / (self.density * 4.0 * std::f32::consts::FRAC_PI_3)).powf(1.0 / 3.0);
     }
 
-    /// Creates a ParticleBundle
-    ///
-    /// ### Arguments
-    /// - color `Color`: The color to render the particle as
-    /// - meshes `&mut ResMut<Assets<Mesh>>`: Mesh resource
-    /// - materials: `&mut ResMut<Assets<ColorMaterial>>: Materials resource
-    pub fn bundle(&self, color: Color, stroke: Option<Stroke>) -> ParticleBundle {
-        return ParticleBundle::new(self.clone(), color, stroke);
-    }
-
-    pub fn add_point(&mut self, p: Vec2) {
-        self.points.push(p);
-        if self.points.len() > self.max_points {
-            self.points.remove(0);
-        }
-    }
-
     /// Gets the position
     pub fn position(&self) -> Vec2 {
         return self.pos;
@@ -142,6 +124,11 @@ impl Particle {
         return self.radius;
     }
 
+    /// Gets the density
+    pub fn density(&self) -> f32 {
+        return self.density;
+    }
+
     /// Adds a force through Newton's Second Law (F = m a)
     ///
     /// i.e. If F = m a, therefore, a = F / m
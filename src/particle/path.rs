@@ -1,24 +1,35 @@
-use bevy::prelude::*;
+use glam::Vec2;
+use std::collections::VecDeque;
 
-#[derive(Component, Default)]
+/// Number of points a new path keeps
+pub const DEFAULT_CAPACITY: usize = 200;
+
+/// The most recent positions of a particle, oldest first
+///
+/// A ring buffer, so adding a point to a full path drops the oldest in constant time
+#[cfg_attr(feature = "app", derive(bevy::prelude::Component))]
+#[derive(Default)]
 pub struct Path {
-    pub points: Vec<Vec2>,
+    pub points: VecDeque<Vec2>,
     max_size: usize,
 }
 
 impl Path {
     pub fn new(max_size: usize) -> Self {
         Self {
-            points: Vec::new(),
+            points: VecDeque::with_capacity(max_size),
             max_size,
         }
     }
 
     pub fn add_point(&mut self, p: Vec2) {
-        self.points.push(p);
-        if self.points.len() > self.max_size {
-            self.points.remove(0);
+        if self.max_size == 0 {
+            return;
         }
+        if self.points.len() >= self.max_size {
+            self.points.pop_front();
+        }
+        self.points.push_back(p);
     }
 
     pub fn size(&self) -> usize {
@@ -29,10 +40,14 @@ impl Path {
         return self.max_size;
     }
 
+    /// Changes how many points are kept, dropping the oldest if there are too many
+    pub fn set_capacity(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        let excess = self.points.len().saturating_sub(max_size);
+        self.points.drain(..excess);
+    }
+
     pub fn reset(&mut self) {
-        if self.points.len() == 0 {
-            return;
-        }
-        self.points = Vec::new();
+        self.points.clear();
     }
 }
@@ -0,0 +1,226 @@
+use crate::particle::Particle;
+use glam::Vec2;
+
+/// Maximum depth of the tree, particles that still share a cell at this depth are
+/// stored together in a single leaf (e.g. coincident particles)
+const MAX_DEPTH: usize = 32;
+
+/// A square region of the quadtree
+struct Node {
+    center: Vec2,
+    half_size: f32,
+    mass: f32,
+    center_of_mass: Vec2,
+    /// Index of the first of 4 consecutive children (0 if the node is a leaf)
+    children: usize,
+    /// Indices of the particles stored in a leaf
+    bodies: Vec<usize>,
+}
+
+impl Node {
+    fn new(center: Vec2, half_size: f32) -> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            center_of_mass: Vec2::ZERO,
+            children: 0,
+            bodies: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        return self.children == 0;
+    }
+
+    fn contains(&self, p: Vec2) -> bool {
+        let d = (p - self.center).abs();
+        return d.x <= self.half_size && d.y <= self.half_size;
+    }
+
+    /// Gets the index (0-3) of the child quadrant a point falls into
+    fn quadrant(&self, p: Vec2) -> usize {
+        let mut q = 0;
+        if p.x >= self.center.x {
+            q += 1;
+        }
+        if p.y >= self.center.y {
+            q += 2;
+        }
+        return q;
+    }
+}
+
+/// Barnes-Hut quadtree used to approximate the gravitational acceleration
+/// of a set of particles in O(n log n)
+pub struct QuadTree {
+    nodes: Vec<Node>,
+    positions: Vec<Vec2>,
+    masses: Vec<f32>,
+    theta: f32,
+}
+
+impl QuadTree {
+    /// Builds a quadtree from a set of particles
+    ///
+    /// ### Arguments
+    /// - `particles` The particles to insert
+    /// - `theta` The opening angle, cells with `size / distance < theta` are approximated by their centre of mass (0.0 is exact)
+    pub fn new(particles: &[Particle], theta: f32) -> Self {
+        let positions: Vec<Vec2> = particles.iter().map(|p| p.position()).collect();
+        let masses: Vec<f32> = particles.iter().map(|p| p.mass()).collect();
+
+        let mut min = Vec2::splat(f32::MAX);
+        let mut max = Vec2::splat(f32::MIN);
+        for p in &positions {
+            min = min.min(*p);
+            max = max.max(*p);
+        }
+        let (center, half_size) = if positions.is_empty() {
+            (Vec2::ZERO, 1.0)
+        } else {
+            (
+                (min + max) / 2.0,
+                ((max - min).max_element() / 2.0).max(1.0) * 1.01,
+            )
+        };
+
+        let mut tree = Self {
+            nodes: vec![Node::new(center, half_size)],
+            positions,
+            masses,
+            theta,
+        };
+        for i in 0..tree.positions.len() {
+            tree.insert(0, i, 0);
+        }
+        tree.summarize(0);
+        return tree;
+    }
+
+    fn insert(&mut self, node: usize, body: usize, depth: usize) {
+        if self.nodes[node].is_leaf() {
+            if self.nodes[node].bodies.is_empty() || depth >= MAX_DEPTH {
+                self.nodes[node].bodies.push(body);
+                return;
+            }
+            self.subdivide(node);
+            let existing = std::mem::take(&mut self.nodes[node].bodies);
+            for b in existing {
+                let child =
+                    self.nodes[node].children + self.nodes[node].quadrant(self.positions[b]);
+                self.insert(child, b, depth + 1);
+            }
+        }
+        let child = self.nodes[node].children + self.nodes[node].quadrant(self.positions[body]);
+        self.insert(child, body, depth + 1);
+    }
+
+    fn subdivide(&mut self, node: usize) {
+        let center = self.nodes[node].center;
+        let h = self.nodes[node].half_size / 2.0;
+        self.nodes[node].children = self.nodes.len();
+        for offset in [
+            Vec2::new(-h, -h),
+            Vec2::new(h, -h),
+            Vec2::new(-h, h),
+            Vec2::new(h, h),
+        ] {
+            self.nodes.push(Node::new(center + offset, h));
+        }
+    }
+
+    /// Computes the mass and centre of mass of every node, bottom up
+    fn summarize(&mut self, node: usize) {
+        let mut mass = 0.0;
+        let mut weighted = Vec2::ZERO;
+        if self.nodes[node].is_leaf() {
+            for &b in &self.nodes[node].bodies {
+                mass += self.masses[b];
+                weighted += self.positions[b] * self.masses[b];
+            }
+        } else {
+            let first = self.nodes[node].children;
+            for child in first..first + 4 {
+                self.summarize(child);
+                mass += self.nodes[child].mass;
+                weighted += self.nodes[child].center_of_mass * self.nodes[child].mass;
+            }
+        }
+        self.nodes[node].mass = mass;
+        self.nodes[node].center_of_mass = if mass > 0.0 {
+            weighted / mass
+        } else {
+            self.nodes[node].center
+        };
+    }
+
+    /// Gets the gravitational acceleration at a point
+    ///
+    /// ### Arguments
+    /// - `pos` The point to evaluate the acceleration at
+    /// - `exclude` The index of a particle to ignore (i.e. the particle at `pos`)
+    /// - `g` The gravitational constant
+    /// - `softening` The Plummer softening length
+    ///
+    /// ### Returns
+    /// `Vec2` The acceleration
+    pub fn acceleration_at(
+        &self,
+        pos: Vec2,
+        exclude: Option<usize>,
+        g: f32,
+        softening: f32,
+    ) -> Vec2 {
+        let mut acc = Vec2::ZERO;
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.mass <= 0.0 {
+                continue;
+            }
+            if node.is_leaf() {
+                for &b in &node.bodies {
+                    if Some(b) == exclude {
+                        continue;
+                    }
+                    acc += super::gravitational_acceleration(
+                        pos,
+                        self.positions[b],
+                        self.masses[b],
+                        g,
+                        softening,
+                    );
+                }
+                continue;
+            }
+            let dist = (node.center_of_mass - pos).length();
+            if !node.contains(pos) && (2.0 * node.half_size) < self.theta * dist {
+                acc += super::gravitational_acceleration(
+                    pos,
+                    node.center_of_mass,
+                    node.mass,
+                    g,
+                    softening,
+                );
+            } else {
+                stack.extend(node.children..node.children + 4);
+            }
+        }
+        return acc;
+    }
+
+    /// Gets the gravitational acceleration of every particle the tree was built from
+    ///
+    /// ### Arguments
+    /// - `g` The gravitational constant
+    /// - `softening` The Plummer softening length
+    ///
+    /// ### Returns
+    /// `Vec<Vec2>` The accelerations, in the same order as the particles
+    pub fn accelerations(&self, g: f32, softening: f32) -> Vec<Vec2> {
+        return (0..self.positions.len())
+            .map(|i| self.acceleration_at(self.positions[i], Some(i), g, softening))
+            .collect();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::particle::Particle;
+use glam::DVec2;
+
+/// Quantities that are conserved by an isolated system of particles
+///
+/// Accumulated in `f64` so that the drift of the simulation is not hidden by rounding
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConservedQuantities {
+    pub kinetic_energy: f64,
+    pub potential_energy: f64,
+    pub momentum: DVec2,
+    /// Angular momentum about the origin (z component)
+    pub angular_momentum: f64,
+    pub center_of_mass: DVec2,
+    pub total_mass: f64,
+    /// Sum of the momentum magnitudes, used to scale the momentum drift
+    momentum_scale: f64,
+    /// Sum of the angular momentum magnitudes, used to scale the angular momentum drift
+    angular_momentum_scale: f64,
+}
+
+impl ConservedQuantities {
+    /// Measures the conserved quantities of a set of particles
+    ///
+    /// ### Arguments
+    /// - `particles` The particles
+    /// - `g` The gravitational constant
+    /// - `softening` The Plummer softening length
+    pub fn measure(particles: &[Particle], g: f32, softening: f32) -> Self {
+        let mut q = Self::default();
+        let mut weighted = DVec2::ZERO;
+        for (i, a) in particles.iter().enumerate() {
+            let m = a.mass() as f64;
+            let pos = a.position().as_dvec2();
+            let vel = a.velocity().as_dvec2();
+
+            q.total_mass += m;
+            weighted += pos * m;
+            q.kinetic_energy += 0.5 * m * vel.length_squared();
+            q.momentum += vel * m;
+            q.momentum_scale += m * vel.length();
+            q.angular_momentum += m * pos.perp_dot(vel);
+            q.angular_momentum_scale += (m * pos.perp_dot(vel)).abs();
+
+            for b in &particles[i + 1..] {
+                q.potential_energy += potential_energy(a, b, g, softening) as f64;
+            }
+        }
+        if q.total_mass > 0.0 {
+            q.center_of_mass = weighted / q.total_mass;
+        }
+        return q;
+    }
+
+    /// Gets the total (kinetic + potential) energy
+    pub fn total_energy(&self) -> f64 {
+        return self.kinetic_energy + self.potential_energy;
+    }
+
+    /// Gets the relative change in total energy since an earlier measurement
+    pub fn energy_drift(&self, initial: &Self) -> f64 {
+        return relative_drift(
+            self.total_energy() - initial.total_energy(),
+            initial.total_energy(),
+        );
+    }
+
+    /// Gets the change in linear momentum since an earlier measurement,
+    /// relative to the sum of the momentum magnitudes
+    pub fn momentum_drift(&self, initial: &Self) -> f64 {
+        return relative_drift(
+            (self.momentum - initial.momentum).length(),
+            initial.momentum_scale,
+        );
+    }
+
+    /// Gets the change in angular momentum since an earlier measurement,
+    /// relative to the sum of the angular momentum magnitudes
+    pub fn angular_momentum_drift(&self, initial: &Self) -> f64 {
+        return relative_drift(
+            self.angular_momentum - initial.angular_momentum,
+            initial.angular_momentum_scale,
+        );
+    }
+}
+
+fn relative_drift(change: f64, scale: f64) -> f64 {
+    if scale.abs() <= f64::EPSILON {
+        return change.abs();
+    }
+    return (change / scale).abs();
+}
+
+/// Gets the gravitational potential energy of a pair of particles
+///
+/// ### Arguments
+/// - `a` Particle
+/// - `b` Particle
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length, the same as used for the force
+///
+/// ### Returns
+/// `f32` The potential energy (negative)
+pub fn potential_energy(a: &Particle, b: &Particle, g: f32, softening: f32) -> f32 {
+    let phi = super::gravitational_potential(a.position(), b.position(), b.mass(), g, softening);
+    return a.mass() * phi;
+}
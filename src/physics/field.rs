@@ -0,0 +1,153 @@
+use crate::particle::Particle;
+use glam::Vec2;
+
+/// The quantity a field overlay shows
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FieldQuantity {
+    /// Gravitational potential, showing the wells
+    #[default]
+    Potential,
+    /// Magnitude of the gravitational field (acceleration), showing saddle points as dark spots
+    Strength,
+}
+
+impl FieldQuantity {
+    pub const ALL: [FieldQuantity; 2] = [FieldQuantity::Potential, FieldQuantity::Strength];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FieldQuantity::Potential => "Potential",
+            FieldQuantity::Strength => "Field Strength",
+        }
+    }
+}
+
+/// The gravitational potential and field sampled on a regular grid
+pub struct FieldGrid {
+    /// Position of the bottom left sample
+    pub origin: Vec2,
+    /// Distance between neighbouring samples
+    pub spacing: Vec2,
+    pub width: usize,
+    pub height: usize,
+    /// Potential at each sample, row by row from the bottom
+    pub potential: Vec<f32>,
+    /// Gravitational acceleration at each sample, in the same order as `potential`
+    pub field: Vec<Vec2>,
+}
+
+impl FieldGrid {
+    /// Samples the field at the centres of a grid of cells covering an area
+    ///
+    /// Uses the same softened force law as `physics::attract`
+    ///
+    /// ### Arguments
+    /// - `particles` The attracting particles
+    /// - `min` The bottom left corner of the area
+    /// - `max` The top right corner of the area
+    /// - `width` The number of cells across (at least 1)
+    /// - `height` The number of cells up (at least 1)
+    /// - `g` The gravitational constant
+    /// - `softening` The Plummer softening length
+    pub fn sample(
+        particles: &[Particle],
+        min: Vec2,
+        max: Vec2,
+        width: usize,
+        height: usize,
+        g: f32,
+        softening: f32,
+    ) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let spacing = (max - min) / Vec2::new(width as f32, height as f32);
+        let mut grid = Self {
+            origin: min + spacing / 2.0,
+            spacing,
+            width,
+            height,
+            potential: Vec::with_capacity(width * height),
+            field: Vec::with_capacity(width * height),
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let pos = grid.position(x, y);
+                let mut potential = 0.0;
+                let mut field = Vec2::ZERO;
+                for p in particles {
+                    potential +=
+                        super::gravitational_potential(pos, p.position(), p.mass(), g, softening);
+                    field += super::gravitational_acceleration(
+                        pos,
+                        p.position(),
+                        p.mass(),
+                        g,
+                        softening,
+                    );
+                }
+                grid.potential.push(potential);
+                grid.field.push(field);
+            }
+        }
+        return grid;
+    }
+
+    /// Gets the position of a sample
+    pub fn position(&self, x: usize, y: usize) -> Vec2 {
+        return self.origin + Vec2::new(x as f32, y as f32) * self.spacing;
+    }
+
+    /// Gets the logarithm (base 10) of the magnitude of a quantity at every sample
+    ///
+    /// Both quantities span many orders of magnitude close to the particles, so they are
+    /// compared logarithmically. Zero magnitudes are clamped to the smallest positive `f32`
+    pub fn magnitudes(&self, quantity: FieldQuantity) -> Vec<f32> {
+        let log = |v: f32| v.abs().max(f32::MIN_POSITIVE).log10();
+        match quantity {
+            FieldQuantity::Potential => self.potential.iter().map(|v| log(*v)).collect(),
+            FieldQuantity::Strength => self.field.iter().map(|v| log(v.length())).collect(),
+        }
+    }
+
+    /// Traces contour lines through values sampled on the grid, with marching squares
+    ///
+    /// ### Arguments
+    /// - `values` A value for every sample, e.g. from `FieldGrid::magnitudes`
+    /// - `levels` The number of contours, evenly spaced between the smallest and largest value
+    ///
+    /// ### Returns
+    /// `Vec<(Vec2, Vec2)>` The line segments making up every contour
+    pub fn contours(&self, values: &[f32], levels: usize) -> Vec<(Vec2, Vec2)> {
+        let (min, max) = values.iter().fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+        let mut segments = Vec::new();
+        if values.len() != self.width * self.height || min >= max {
+            return segments;
+        }
+        let at = |x: usize, y: usize| (self.position(x, y), values[y * self.width + x]);
+        for level in 1..=levels {
+            let level = min + (max - min) * level as f32 / (levels + 1) as f32;
+            for y in 0..self.height.saturating_sub(1) {
+                for x in 0..self.width.saturating_sub(1) {
+                    // Edges of the square in order: bottom, right, top, left
+                    let corners = [at(x, y), at(x + 1, y), at(x + 1, y + 1), at(x, y + 1)];
+                    let crossings: Vec<Vec2> = (0..4)
+                        .filter_map(|i| {
+                            let (a, va) = corners[i];
+                            let (b, vb) = corners[(i + 1) % 4];
+                            if (va < level) == (vb < level) {
+                                return None;
+                            }
+                            return Some(a.lerp(b, (level - va) / (vb - va)));
+                        })
+                        .collect();
+                    // Two crossings, or four at a saddle (paired in edge order)
+                    for pair in crossings.chunks_exact(2) {
+                        segments.push((pair[0], pair[1]));
+                    }
+                }
+            }
+        }
+        return segments;
+    }
+}
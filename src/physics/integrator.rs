@@ -0,0 +1,193 @@
+use crate::particle::Particle;
+use glam::Vec2;
+use std::str::FromStr;
+
+/// Function used by integrators to evaluate the acceleration of every particle at a given state
+pub type AccelerationFn<'a> = dyn Fn(&[Particle]) -> Vec<Vec2> + 'a;
+
+/// A numerical method for advancing particles through time
+pub trait Integrator {
+    /// Advances the particles by one timestep
+    ///
+    /// The acceleration of each particle is left set to the last acceleration evaluated for it,
+    /// except by `RungeKutta4` (see its docs)
+    ///
+    /// ### Arguments
+    /// - `particles` The particles to advance
+    /// - `dt` The timestep
+    /// - `accelerations` Evaluates the acceleration of every particle at a given state
+    fn step(&self, particles: &mut [Particle], dt: f32, accelerations: &AccelerationFn);
+}
+
+/// First order, symplectic. One force evaluation per step
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(&self, particles: &mut [Particle], dt: f32, accelerations: &AccelerationFn) {
+        let acc = accelerations(particles);
+        for (p, a) in particles.iter_mut().zip(acc) {
+            p.set_acc(a);
+            p.set_vel(super::integrate(p.velocity(), a, dt));
+            p.set_pos(super::integrate(p.position(), p.velocity(), dt));
+        }
+    }
+}
+
+/// Second order, symplectic. Kick-drift-kick leapfrog, two force evaluations per step
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn step(&self, particles: &mut [Particle], dt: f32, accelerations: &AccelerationFn) {
+        let acc = accelerations(particles);
+        for (p, a) in particles.iter_mut().zip(acc) {
+            p.set_vel(p.velocity() + a * (0.5 * dt));
+            p.set_pos(p.position() + p.velocity() * dt);
+        }
+        let acc = accelerations(particles);
+        for (p, a) in particles.iter_mut().zip(acc) {
+            p.set_acc(a);
+            p.set_vel(p.velocity() + a * (0.5 * dt));
+        }
+    }
+}
+
+/// Classic fourth order Runge-Kutta. Not symplectic, four force evaluations per step
+///
+/// Its later evaluations are at trial positions the particles never reach, so each particle is left
+/// with the acceleration at the start of the step rather than the last one evaluated. Getting it at
+/// the end of the step would take a fifth evaluation
+pub struct RungeKutta4;
+
+impl RungeKutta4 {
+    /// Gets a copy of the particles displaced by `vel * h`
+    fn offset(particles: &[Particle], vel: &[Vec2], h: f32) -> Vec<Particle> {
+        return particles
+            .iter()
+            .zip(vel)
+            .map(|(p, v)| {
+                let mut moved = p.clone();
+                moved.set_pos(p.position() + *v * h);
+                moved
+            })
+            .collect();
+    }
+}
+
+impl Integrator for RungeKutta4 {
+    fn step(&self, particles: &mut [Particle], dt: f32, accelerations: &AccelerationFn) {
+        let half = 0.5 * dt;
+        let v0: Vec<Vec2> = particles.iter().map(|p| p.velocity()).collect();
+
+        let k1v = accelerations(particles);
+        let k1x = v0.clone();
+
+        let k2v = accelerations(&Self::offset(particles, &k1x, half));
+        let k2x: Vec<Vec2> = v0.iter().zip(&k1v).map(|(v, a)| *v + *a * half).collect();
+
+        let k3v = accelerations(&Self::offset(particles, &k2x, half));
+        let k3x: Vec<Vec2> = v0.iter().zip(&k2v).map(|(v, a)| *v + *a * half).collect();
+
+        let k4v = accelerations(&Self::offset(particles, &k3x, dt));
+        let k4x: Vec<Vec2> = v0.iter().zip(&k3v).map(|(v, a)| *v + *a * dt).collect();
+
+        for (i, p) in particles.iter_mut().enumerate() {
+            let dx = (k1x[i] + 2.0 * k2x[i] + 2.0 * k3x[i] + k4x[i]) * (dt / 6.0);
+            let dv = (k1v[i] + 2.0 * k2v[i] + 2.0 * k3v[i] + k4v[i]) * (dt / 6.0);
+            p.set_pos(p.position() + dx);
+            p.set_vel(p.velocity() + dv);
+            // The start of the step, see the docs above
+            p.set_acc(k1v[i]);
+        }
+    }
+}
+
+/// Fourth order, symplectic. Yoshida's composition of three leapfrog steps, three force evaluations per step
+pub struct Yoshida4;
+
+impl Yoshida4 {
+    const W0: f64 = -1.259_921_049_894_873_2 / (2.0 - 1.259_921_049_894_873_2);
+    const W1: f64 = 1.0 / (2.0 - 1.259_921_049_894_873_2);
+    const C: [f64; 4] = [
+        Self::W1 / 2.0,
+        (Self::W0 + Self::W1) / 2.0,
+        (Self::W0 + Self::W1) / 2.0,
+        Self::W1 / 2.0,
+    ];
+    const D: [f64; 3] = [Self::W1, Self::W0, Self::W1];
+}
+
+impl Integrator for Yoshida4 {
+    fn step(&self, particles: &mut [Particle], dt: f32, accelerations: &AccelerationFn) {
+        for stage in 0..4 {
+            let c = (Self::C[stage] as f32) * dt;
+            for p in particles.iter_mut() {
+                p.set_pos(p.position() + p.velocity() * c);
+            }
+            if stage == 3 {
+                break;
+            }
+            let d = (Self::D[stage] as f32) * dt;
+            let acc = accelerations(particles);
+            for (p, a) in particles.iter_mut().zip(acc) {
+                p.set_acc(a);
+                p.set_vel(p.velocity() + a * d);
+            }
+        }
+    }
+}
+
+/// The integrators that can be selected at runtime
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum IntegratorKind {
+    #[default]
+    SemiImplicitEuler,
+    VelocityVerlet,
+    RungeKutta4,
+    Yoshida4,
+}
+
+impl IntegratorKind {
+    pub const ALL: [IntegratorKind; 4] = [
+        IntegratorKind::SemiImplicitEuler,
+        IntegratorKind::VelocityVerlet,
+        IntegratorKind::RungeKutta4,
+        IntegratorKind::Yoshida4,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IntegratorKind::SemiImplicitEuler => "Semi-implicit Euler",
+            IntegratorKind::VelocityVerlet => "Velocity Verlet",
+            IntegratorKind::RungeKutta4 => "Runge-Kutta 4",
+            IntegratorKind::Yoshida4 => "Yoshida 4",
+        }
+    }
+
+    /// Short name used on the command line and in files
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegratorKind::SemiImplicitEuler => "euler",
+            IntegratorKind::VelocityVerlet => "verlet",
+            IntegratorKind::RungeKutta4 => "rk4",
+            IntegratorKind::Yoshida4 => "yoshida",
+        }
+    }
+
+    /// Gets the integrator implementation
+    pub fn integrator(&self) -> &'static dyn Integrator {
+        match self {
+            IntegratorKind::SemiImplicitEuler => &SemiImplicitEuler,
+            IntegratorKind::VelocityVerlet => &VelocityVerlet,
+            IntegratorKind::RungeKutta4 => &RungeKutta4,
+            IntegratorKind::Yoshida4 => &Yoshida4,
+        }
+    }
+}
+
+impl FromStr for IntegratorKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return super::from_name(&Self::ALL, s, Self::name);
+    }
+}
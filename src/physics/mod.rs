@@ -1,8 +1,122 @@
+pub mod barnes_hut;
+pub mod diagnostics;
+pub mod field;
+pub mod integrator;
+pub mod orbit;
+
 use crate::particle::Particle;
-use bevy::prelude::*;
+use glam::Vec2;
+use log::warn;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Method used to calculate the gravitational forces between particles
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ForceSolver {
+    /// Exact O(n²) summation over every pair of particles
+    #[default]
+    Direct,
+    /// O(n log n) approximation with a quadtree
+    BarnesHut,
+}
+
+impl ForceSolver {
+    pub const ALL: [ForceSolver; 2] = [ForceSolver::Direct, ForceSolver::BarnesHut];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForceSolver::Direct => "Direct Summation",
+            ForceSolver::BarnesHut => "Barnes-Hut",
+        }
+    }
+
+    /// Short name used on the command line and in files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForceSolver::Direct => "direct",
+            ForceSolver::BarnesHut => "barnes-hut",
+        }
+    }
+}
+
+impl FromStr for ForceSolver {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return from_name(&Self::ALL, s, Self::name);
+    }
+}
+
+/// How intersecting particles are resolved
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CollisionMode {
+    /// Particles bounce off each other with the co-efficient of restitution
+    #[default]
+    Bounce,
+    /// Particles stick together into a single particle (perfectly inelastic)
+    Merge,
+}
+
+impl CollisionMode {
+    pub const ALL: [CollisionMode; 2] = [CollisionMode::Bounce, CollisionMode::Merge];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CollisionMode::Bounce => "Bounce",
+            CollisionMode::Merge => "Merge",
+        }
+    }
+
+    /// Short name used on the command line and in files
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollisionMode::Bounce => "bounce",
+            CollisionMode::Merge => "merge",
+        }
+    }
+}
+
+impl FromStr for CollisionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return from_name(&Self::ALL, s, Self::name);
+    }
+}
+
+/// Finds the option with a given short name
+///
+/// ### Arguments
+/// - `options` All of the options
+/// - `s` The name to look for
+/// - `name` Gets the short name of an option
+pub(crate) fn from_name<T: Copy>(
+    options: &[T],
+    s: &str,
+    name: fn(&T) -> &'static str,
+) -> anyhow::Result<T> {
+    if let Some(option) = options.iter().find(|o| name(o) == s) {
+        return Ok(*option);
+    }
+    let names: Vec<&str> = options.iter().map(name).collect();
+    return Err(anyhow::anyhow!(
+        "unknown option '{}', expected one of: {}",
+        s,
+        names.join(", ")
+    ));
+}
+
+/// A particle absorbed into another by a merging collision
+///
+/// Indices refer to the order of the particles before the absorbed particles were removed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Merge {
+    pub survivor: usize,
+    pub absorbed: usize,
+}
 
 /// Integrates a vector with respect to time given it's first derivative
-/// Uses semi-implicit Euler integration
+/// Uses a single Euler step
 ///
 /// ### Arguments
 /// - `v` The vector to integrate
@@ -15,17 +129,6 @@ pub fn integrate(v: Vec2, dv: Vec2, dt: f32) -> Vec2 {
     v + dv * dt
 }
 
-/// Updates a particle's physics with semi-implicit Euler integration
-///
-/// ### Arguments
-/// - `p` The particle to update
-/// - `dt` The timestep
-pub fn update_particle(p: &mut Particle, dt: f32) {
-    p.set_vel(integrate(p.velocity(), p.acceleration(), dt));
-    p.set_pos(integrate(p.position(), p.velocity(), dt));
-    p.set_acc(Vec2::ZERO);
-}
-
 /// Checks if two particles are intersection
 ///
 /// ### Arguments
@@ -43,49 +146,236 @@ pub fn is_intersecting(a: &Particle, b: &Particle) -> bool {
     return false;
 }
 
-/// Resolves the position of a particle to remove intersections  
-///   
+/// Gets the unit vector pointing from `a` to `b`
+///
+/// Coincident particles have no separating direction, so they are separated along x
+fn contact_normal(a: &Particle, b: &Particle) -> Vec2 {
+    let d = b.position() - a.position();
+    let dist = d.length();
+    if dist > 0.0 {
+        return d / dist;
+    }
+    return Vec2::X;
+}
+
+/// Resolves the positions of two particles to remove their intersection
+///
+/// Both particles are pushed apart along the line between their centres, the lighter particle
+/// moves further so that the centre of mass stays in place
+///
 /// ***NOTE***: does not check for intersection
 ///
 /// ### Arguments
-/// - `a` The particle that will be moved
+/// - `a` Particle
 /// - `b` The particle that is intersecting
-pub fn resolve_intersection(a: &mut Particle, b: &Particle) {
-    let d = b.position() - a.position();
-    let dist = d.length();
-    let combined_radii = a.radius() + b.radius();
-    let overlap = 0.5 * (combined_radii - dist);
-    let normal = d / dist;
+pub fn resolve_intersection(a: &mut Particle, b: &mut Particle) {
+    let normal = contact_normal(a, b);
+    let dist = (b.position() - a.position()).length();
+    let overlap = a.radius() + b.radius() - dist;
+    let (a_inv_mass, b_inv_mass) = inverse_masses(a, b);
 
     let prev = a.position();
-    a.set_pos(prev + normal * overlap);
+    a.set_pos(prev - normal * overlap * a_inv_mass);
+    let prev = b.position();
+    b.set_pos(prev + normal * overlap * b_inv_mass);
+}
+
+/// Gets the share of a correction each particle takes, in proportion to its inverse mass
+fn inverse_masses(a: &Particle, b: &Particle) -> (f32, f32) {
+    let a_inv_mass = if a.mass() > 0.0 { 1.0 / a.mass() } else { 0.0 };
+    let b_inv_mass = if b.mass() > 0.0 { 1.0 / b.mass() } else { 0.0 };
+    let total = a_inv_mass + b_inv_mass;
+    if total <= 0.0 {
+        return (0.5, 0.5);
+    }
+    return (a_inv_mass / total, b_inv_mass / total);
 }
 
-/// Resolves the velocity of a particle after an elastic collision
+/// Resolves the velocities of two particles after a collision
+///
+/// Applies equal and opposite impulses along the contact normal, so momentum is conserved and the
+/// kinetic energy of the relative motion along the normal is scaled by `r²`
 ///
 /// ***NOTE***: does not check for intersection
 ///
 /// ### Arguments
-/// - `a` The particle that will be moved
+/// - `a` Particle
 /// - `b` The particle that is colliding
 /// - `r` The co-efficient of restitution [0.0 - 1.0] (values outside of the range may produce unexpected results)
-pub fn resolve_collision(a: &mut Particle, b: &Particle, r: f32) {
-    if r < 0.0 || r > 1.0 {
+pub fn resolve_collision(a: &mut Particle, b: &mut Particle, r: f32) {
+    if !(0.0..=1.0).contains(&r) {
         warn!("Co-efficient of restitution, 'r' = {:.1}, is outside of the range, [0.0 - 1.0]. May produce unexpected results!", r);
     }
-    let normal = (a.position() - b.position()).normalize();
-    let rel_vel = a.velocity() - b.velocity();
+    let normal = contact_normal(a, b);
+    let rel_vel = b.velocity() - a.velocity();
     let col_normal = rel_vel.dot(normal);
-    if col_normal < 0.0 {
-        let a_inv_mass = 1.0 / a.mass();
-        let b_inv_mass = 1.0 / b.mass();
+    // Only resolve particles that are moving towards each other
+    if col_normal >= 0.0 {
+        return;
+    }
+    let a_inv_mass = 1.0 / a.mass();
+    let b_inv_mass = 1.0 / b.mass();
+    if !(a_inv_mass + b_inv_mass).is_finite() {
+        return;
+    }
+
+    let impulse_mag = (-(1.0 + r) * col_normal) / (a_inv_mass + b_inv_mass);
+    let impulse = normal * impulse_mag;
+
+    let prev = a.velocity();
+    a.set_vel(prev - impulse * a_inv_mass);
+    let prev = b.velocity();
+    b.set_vel(prev + impulse * b_inv_mass);
+}
+
+/// Merges a particle into another with a perfectly inelastic collision
+///
+/// Mass and momentum are conserved, the merged particle is placed at the centre of mass and keeps
+/// the density of `a`, so its volume grows to hold the combined mass
+///
+/// ***NOTE***: does not check for intersection
+///
+/// ### Arguments
+/// - `a` The particle that survives the merge
+/// - `b` The particle that is absorbed
+pub fn merge(a: &mut Particle, b: &Particle) {
+    let (ma, mb) = (a.mass(), b.mass());
+    let total = ma + mb;
+    if total <= 0.0 {
+        return;
+    }
+    a.set_pos((a.position() * ma + b.position() * mb) / total);
+    a.set_vel((a.velocity() * ma + b.velocity() * mb) / total);
+    a.set_acc((a.acceleration() * ma + b.acceleration() * mb) / total);
+    a.set_mass_with_radius(total);
+}
 
-        let impulse_mag = (-(1.0 + r) * col_normal) / (a_inv_mass + b_inv_mass);
-        let impulse = normal + impulse_mag;
+/// Finds every pair of intersecting particles, in the order a loop over every pair visits them
+///
+/// Particles are binned in a uniform grid with cells as wide as the largest particle, so only
+/// particles in the same or neighbouring cells are compared. This keeps the cost close to linear in
+/// the number of particles, unless a few particles are much larger than the rest
+///
+/// ### Arguments
+/// - `particles` The particles
+///
+/// ### Returns
+/// `Vec<(usize, usize)>` The indices of each intersecting pair, the lower index first
+pub fn collision_pairs(particles: &[Particle]) -> Vec<(usize, usize)> {
+    let max_radius = particles.iter().map(|p| p.radius()).fold(0.0, f32::max);
+    if particles.len() < 2 || max_radius <= 0.0 || !max_radius.is_finite() {
+        return Vec::new();
+    }
+    // Intersecting particles are less than two of the largest radii apart
+    let cell_size = 2.0 * max_radius;
+    let cell = |p: &Particle| {
+        let c = (p.position() / cell_size).floor();
+        (c.x as i64, c.y as i64)
+    };
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, p) in particles.iter().enumerate() {
+        grid.entry(cell(p)).or_default().push(i);
+    }
 
-        let prev = a.velocity();
-        a.set_vel(prev + impulse * a_inv_mass);
+    let mut pairs = Vec::new();
+    for (i, a) in particles.iter().enumerate() {
+        let (x, y) = cell(a);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let Some(others) = grid.get(&(x.saturating_add(dx), y.saturating_add(dy))) else {
+                    continue;
+                };
+                for &j in others {
+                    if j > i && is_intersecting(a, &particles[j]) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    return pairs;
+}
+
+/// Bounces every pair of intersecting particles off each other
+///
+/// Pairs are found with `collision_pairs` before any are resolved, so a particle pushed into
+/// another one is resolved on the next step
+///
+/// ### Arguments
+/// - `particles` The particles
+/// - `r` The co-efficient of restitution
+pub fn bounce_collisions(particles: &mut [Particle], r: f32) {
+    for (i, j) in collision_pairs(particles) {
+        let (head, tail) = particles.split_at_mut(j);
+        let (a, b) = (&mut head[i], &mut tail[0]);
+        // An earlier pair may have pushed them apart
+        if is_intersecting(a, b) {
+            resolve_intersection(a, b);
+            resolve_collision(a, b, r);
+        }
+    }
+}
+
+/// Merges every pair of intersecting particles, the lighter particle is absorbed into the heavier one
+/// and removed
+///
+/// Pairs are found with `collision_pairs` before any are merged, so a particle that grows into
+/// another one merges with it on the next step
+///
+/// ### Arguments
+/// - `particles` The particles
+///
+/// ### Returns
+/// `Vec<Merge>` The merges that happened, in order
+pub fn merge_collisions(particles: &mut Vec<Particle>) -> Vec<Merge> {
+    let mut merges = Vec::new();
+    let mut absorbed = vec![false; particles.len()];
+    for (i, j) in collision_pairs(particles) {
+        if absorbed[i] || absorbed[j] {
+            continue;
+        }
+        let (head, tail) = particles.split_at_mut(j);
+        let (a, b) = (&mut head[i], &mut tail[0]);
+        // An earlier merge may have moved the survivor away
+        if !is_intersecting(a, b) {
+            continue;
+        }
+        if a.mass() >= b.mass() {
+            merge(a, b);
+            absorbed[j] = true;
+            merges.push(Merge {
+                survivor: i,
+                absorbed: j,
+            });
+        } else {
+            merge(b, a);
+            absorbed[i] = true;
+            merges.push(Merge {
+                survivor: j,
+                absorbed: i,
+            });
+        }
     }
+    remove_absorbed(particles, &merges);
+    return merges;
+}
+
+/// Removes the absorbed entries from a list that is in the same order as the merged particles
+///
+/// ### Arguments
+/// - `items` The list to remove from (e.g. the particles, or anything stored alongside them)
+/// - `merges` The merges returned by `merge_collisions`
+pub fn remove_absorbed<T>(items: &mut Vec<T>, merges: &[Merge]) {
+    if merges.is_empty() {
+        return;
+    }
+    let mut i = 0;
+    items.retain(|_| {
+        let keep = !merges.iter().any(|m| m.absorbed == i);
+        i += 1;
+        keep
+    });
 }
 
 /// Attracts a particle to another due to the gravitational force
@@ -93,35 +383,119 @@ pub fn resolve_collision(a: &mut Particle, b: &Particle, r: f32) {
 /// ### Arguments
 /// - `a` The particle that will be attracted (force added)
 /// - `b` The particle that is atracting
-pub fn attract(a: &mut Particle, b: &Particle, g: f32) {
-    let d = b.position() - a.position();
-    let dist = d.length();
-    let mag = (g * a.mass() * b.mass()) / (dist.powf(2.0));
-    let f = d.normalize() * mag;
-    a.add_force(f);
-}
-
-pub fn generate_particle_grid(center: Vec2, n: usize, radius: f32, density: f32) -> Vec<Particle> {
-    let mut particles: Vec<Particle> = Vec::new();
-    let n_f = n as f32;
-    let total_size = (n_f * 2.0 * radius) + 2.0 * radius;
-    let start = Vec2::new(
-        center.x - (total_size / 2.0) + radius,
-        center.x - (total_size / 2.0) + radius,
-    );
-    for i in 0..n {
-        for j in 0..n {
-            let x = start.x + 2.0 * radius * (j as f32);
-            let y = start.y + 2.0 * radius * (i as f32);
-            let mut p = Particle::default();
-            p.set_radius(radius).unwrap();
-            p.set_density(density).unwrap();
-            p.set_pos(Vec2::new(x, y));
-            particles.push(p);
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length
+pub fn attract(a: &mut Particle, b: &Particle, g: f32, softening: f32) {
+    let acc = gravitational_acceleration(a.position(), b.position(), b.mass(), g, softening);
+    a.add_force(acc * a.mass());
+}
+
+/// Gets the gravitational acceleration at a point due to a mass
+///
+/// Uses Plummer softening, i.e. `a = G m d / (|d|² + ε²)^(3/2)`, so that close encounters
+/// produce a finite force. Coincident points (with no softening) produce no force
+///
+/// ### Arguments
+/// - `pos` The point being attracted
+/// - `source` The position of the attracting mass
+/// - `mass` The attracting mass
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length (ε)
+///
+/// ### Returns
+/// `Vec2` The acceleration
+pub fn gravitational_acceleration(
+    pos: Vec2,
+    source: Vec2,
+    mass: f32,
+    g: f32,
+    softening: f32,
+) -> Vec2 {
+    let d = source - pos;
+    let dist_sq = d.length_squared() + softening * softening;
+    if dist_sq <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    return d * ((g * mass) / (dist_sq * dist_sq.sqrt()));
+}
+
+/// Gets the gravitational potential (energy per unit mass) at a point due to a mass
+///
+/// Uses the same Plummer softening as `gravitational_acceleration`, i.e. `φ = -G m / sqrt(|d|² + ε²)`
+///
+/// ### Arguments
+/// - `pos` The point to evaluate the potential at
+/// - `source` The position of the attracting mass
+/// - `mass` The attracting mass
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length (ε)
+///
+/// ### Returns
+/// `f32` The potential (negative)
+pub fn gravitational_potential(pos: Vec2, source: Vec2, mass: f32, g: f32, softening: f32) -> f32 {
+    let dist_sq = (source - pos).length_squared() + softening * softening;
+    if dist_sq <= f32::EPSILON {
+        return 0.0;
+    }
+    return -(g * mass) / dist_sq.sqrt();
+}
+
+/// Gets the gravitational acceleration of every particle due to all of the others
+///
+/// ### Arguments
+/// - `particles` The particles
+/// - `g` The gravitational constant
+/// - `softening` The Plummer softening length
+/// - `solver` The method used to calculate the forces
+/// - `theta` The Barnes-Hut opening angle (ignored by `ForceSolver::Direct`)
+///
+/// ### Returns
+/// `Vec<Vec2>` The accelerations, in the same order as the particles
+pub fn accelerations(
+    particles: &[Particle],
+    g: f32,
+    softening: f32,
+    solver: ForceSolver,
+    theta: f32,
+) -> Vec<Vec2> {
+    match solver {
+        ForceSolver::Direct => particles
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let mut acc = Vec2::ZERO;
+                for (j, b) in particles.iter().enumerate() {
+                    if i != j {
+                        acc += gravitational_acceleration(
+                            a.position(),
+                            b.position(),
+                            b.mass(),
+                            g,
+                            softening,
+                        );
+                    }
+                }
+                acc
+            })
+            .collect(),
+        ForceSolver::BarnesHut => {
+            barnes_hut::QuadTree::new(particles, theta).accelerations(g, softening)
         }
     }
+}
 
-    return particles;
+/// Gets the mass weighted mean position of the particles, `None` if there is no mass
+pub fn center_of_mass(particles: &[Particle]) -> Option<Vec2> {
+    let mut total = 0.0;
+    let mut weighted = Vec2::ZERO;
+    for p in particles {
+        total += p.mass();
+        weighted += p.position() * p.mass();
+    }
+    if total <= 0.0 {
+        return None;
+    }
+    return Some(weighted / total);
 }
 
 pub fn orbital_velocity(satellite: Vec2, planet: Vec2, planet_mass: f32, g: f32) -> Vec2 {
@@ -0,0 +1,164 @@
+use crate::particle::Particle;
+use glam::Vec2;
+
+/// Keplerian orbital elements of a body relative to another, treating the pair as a two-body problem
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitalElements {
+    /// Negative for unbound (hyperbolic) orbits
+    pub semi_major_axis: f32,
+    /// 0 for circular, below 1 for elliptical and 1 or above for unbound orbits
+    pub eccentricity: f32,
+    /// Closest distance to the primary
+    pub periapsis: f32,
+    /// Furthest distance from the primary, `None` for unbound orbits
+    pub apoapsis: Option<f32>,
+    /// Time taken for one orbit, `None` for unbound orbits
+    pub period: Option<f32>,
+    /// Angle of the periapsis from the x axis, in radians
+    pub argument_of_periapsis: f32,
+    /// Specific orbital energy (energy per unit reduced mass)
+    pub specific_energy: f32,
+    /// `true` for anti-clockwise orbits
+    pub prograde: bool,
+}
+
+impl OrbitalElements {
+    /// Calculates the orbital elements of a body relative to a primary
+    ///
+    /// ### Arguments
+    /// - `body` The orbiting body
+    /// - `primary` The body being orbited
+    /// - `g` The gravitational constant
+    ///
+    /// ### Returns
+    /// `Option<OrbitalElements>` The elements, `None` if the bodies coincide or have no mass
+    pub fn new(body: &Particle, primary: &Particle, g: f32) -> Option<Self> {
+        let mu = g * (body.mass() + primary.mass());
+        let r = body.position() - primary.position();
+        let v = body.velocity() - primary.velocity();
+        let dist = r.length();
+        if mu <= 0.0 || dist <= f32::EPSILON {
+            return None;
+        }
+
+        let h = r.perp_dot(v);
+        let energy = 0.5 * v.length_squared() - mu / dist;
+        let e_vec = ((v.length_squared() - mu / dist) * r - r.dot(v) * v) / mu;
+        let e = e_vec.length();
+        let bound = energy < 0.0;
+        let a = -mu / (2.0 * energy);
+        return Some(Self {
+            semi_major_axis: a,
+            eccentricity: e,
+            periapsis: h * h / (mu * (1.0 + e)),
+            apoapsis: if bound { Some(a * (1.0 + e)) } else { None },
+            period: if bound {
+                Some(std::f32::consts::TAU * (a * a * a / mu).sqrt())
+            } else {
+                None
+            },
+            argument_of_periapsis: if e > f32::EPSILON {
+                e_vec.y.atan2(e_vec.x)
+            } else {
+                0.0
+            },
+            specific_energy: energy,
+            prograde: h >= 0.0,
+        });
+    }
+}
+
+/// Finds the body with the strongest gravitational pull on a particle
+///
+/// ### Arguments
+/// - `particles` Every particle
+/// - `index` The particle being pulled
+///
+/// ### Returns
+/// `Option<usize>` The index of the dominant body, `None` if there are no other particles
+pub fn dominant_body(particles: &[Particle], index: usize) -> Option<usize> {
+    let pos: Vec2 = particles[index].position();
+    return particles
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != index)
+        .map(|(j, p)| {
+            let dist_sq = p.position().distance_squared(pos).max(f32::EPSILON);
+            (j, p.mass() / dist_sq)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(j, _)| j);
+}
+
+/// How the body a new particle orbits is chosen
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OrbitTarget {
+    /// The closest particle
+    #[default]
+    Nearest,
+    /// The heaviest particle
+    MostMassive,
+}
+
+impl OrbitTarget {
+    pub const ALL: [OrbitTarget; 2] = [OrbitTarget::Nearest, OrbitTarget::MostMassive];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrbitTarget::Nearest => "Nearest",
+            OrbitTarget::MostMassive => "Most Massive",
+        }
+    }
+
+    /// Finds the body to orbit
+    ///
+    /// ### Arguments
+    /// - `particles` The particles that could be orbited
+    /// - `pos` Where the new particle is placed
+    ///
+    /// ### Returns
+    /// `Option<usize>` The index of the body, `None` if there are no particles
+    pub fn find(&self, particles: &[Particle], pos: Vec2) -> Option<usize> {
+        let key = |p: &Particle| match self {
+            OrbitTarget::Nearest => -p.position().distance_squared(pos),
+            OrbitTarget::MostMassive => p.mass(),
+        };
+        return particles
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| key(a).total_cmp(&key(b)))
+            .map(|(i, _)| i);
+    }
+}
+
+/// Gets the velocity that puts a body on an orbit with its periapsis at its current position
+///
+/// ### Arguments
+/// - `pos` The position of the body (the periapsis)
+/// - `mass` The mass of the body
+/// - `primary` The body to orbit, its velocity is added so the orbit moves with it
+/// - `g` The gravitational constant
+/// - `eccentricity` 0 for a circular orbit, up to (but not including) 1 for elliptical orbits
+/// - `prograde` `true` to orbit anti-clockwise
+///
+/// ### Returns
+/// `Vec2` The velocity, just the primary's velocity if the body is on top of it
+pub fn periapsis_velocity(
+    pos: Vec2,
+    mass: f32,
+    primary: &Particle,
+    g: f32,
+    eccentricity: f32,
+    prograde: bool,
+) -> Vec2 {
+    let r = pos - primary.position();
+    let dist = r.length();
+    if dist <= f32::EPSILON {
+        return primary.velocity();
+    }
+    let mu = g * (primary.mass() + mass);
+    let speed = (mu * (1.0 + eccentricity.max(0.0)) / dist).sqrt();
+    let tangent = r.perp() / dist;
+    let direction = if prograde { 1.0 } else { -1.0 };
+    return primary.velocity() + tangent * speed * direction;
+}
@@ -0,0 +1,187 @@
+use crate::particle::Particle;
+use crate::scenario::{Scenario, ScenarioConstants};
+use glam::Vec2;
+
+/// Colours of the three bodies, so their paths can be told apart
+const COLORS: [[f32; 4]; 3] = [
+    [0.9, 0.3, 0.3, 1.0],
+    [0.3, 0.9, 0.3, 1.0],
+    [0.3, 0.5, 1.0, 1.0],
+];
+
+/// Famous periodic solutions of the equal mass three-body problem
+///
+/// Initial conditions are in normalised units (G = 1, every mass = 1) and are scaled to the
+/// simulation's units by `Preset::particles`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Preset {
+    /// Chenciner & Montgomery's figure-eight, with Simó's initial conditions
+    #[default]
+    FigureEight,
+    /// Lagrange's equilateral triangle, rotating rigidly
+    Lagrange,
+    /// Euler's collinear solution, with one body at rest in the middle
+    Euler,
+    /// Šuvakov & Dmitrašinović's butterfly I
+    ButterflyI,
+    /// Šuvakov & Dmitrašinović's butterfly II
+    ButterflyII,
+    /// Šuvakov & Dmitrašinović's goggles
+    Goggles,
+    /// Šuvakov & Dmitrašinović's moth I
+    MothI,
+    /// Šuvakov & Dmitrašinović's yin-yang Ia
+    YinYang,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 8] = [
+        Preset::FigureEight,
+        Preset::Lagrange,
+        Preset::Euler,
+        Preset::ButterflyI,
+        Preset::ButterflyII,
+        Preset::Goggles,
+        Preset::MothI,
+        Preset::YinYang,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Preset::FigureEight => "Figure-eight",
+            Preset::Lagrange => "Lagrange Triangle",
+            Preset::Euler => "Euler Collinear",
+            Preset::ButterflyI => "Butterfly I",
+            Preset::ButterflyII => "Butterfly II",
+            Preset::Goggles => "Goggles",
+            Preset::MothI => "Moth I",
+            Preset::YinYang => "Yin-yang Ia",
+        }
+    }
+
+    /// The period in normalised units
+    pub fn period(&self) -> f32 {
+        match self {
+            Preset::FigureEight => 6.324_449,
+            Preset::Lagrange => std::f32::consts::TAU / 3f32.sqrt(),
+            Preset::Euler => std::f32::consts::TAU / 1.25f32.sqrt(),
+            Preset::ButterflyI => 6.235_641,
+            Preset::ButterflyII => 7.003_906,
+            Preset::Goggles => 10.466_818,
+            Preset::MothI => 14.893_911,
+            Preset::YinYang => 17.328_36,
+        }
+    }
+
+    /// The positions and velocities of the three bodies in normalised units
+    pub fn normalised(&self) -> [(Vec2, Vec2); 3] {
+        match self {
+            Preset::FigureEight => {
+                let x = Vec2::new(0.970_004_4, -0.243_087_53);
+                let v = Vec2::new(-0.932_407_4, -0.864_731_46);
+                [(x, -v / 2.0), (-x, -v / 2.0), (Vec2::ZERO, v)]
+            }
+            Preset::Lagrange => {
+                // Unit sides, rotating at ω = sqrt(G 3m / s³)
+                let omega = 3f32.sqrt();
+                let r = 1.0 / 3f32.sqrt();
+                let body = |i: usize| {
+                    let pos = Vec2::from_angle(std::f32::consts::TAU * i as f32 / 3.0) * r;
+                    (pos, pos.perp() * omega)
+                };
+                [body(0), body(1), body(2)]
+            }
+            Preset::Euler => {
+                // Each outer body feels G m / d² + G m / (2d)², so ω² = 5/4 for d = 1
+                let v = Vec2::new(0.0, 1.25f32.sqrt());
+                [(Vec2::NEG_X, -v), (Vec2::ZERO, Vec2::ZERO), (Vec2::X, v)]
+            }
+            Preset::ButterflyI => Self::isosceles(0.306_893, 0.125_507),
+            Preset::ButterflyII => Self::isosceles(0.392_955, 0.097_579),
+            Preset::Goggles => Self::isosceles(0.083_300, 0.127_889),
+            Preset::MothI => Self::isosceles(0.464_445, 0.396_060),
+            Preset::YinYang => Self::isosceles(0.513_938, 0.304_736),
+        }
+    }
+
+    /// Šuvakov & Dmitrašinović's initial conditions, bodies at (-1, 0), (1, 0) and (0, 0)
+    /// with velocities (p1, p2), (p1, p2) and (-2 p1, -2 p2)
+    fn isosceles(p1: f32, p2: f32) -> [(Vec2, Vec2); 3] {
+        let v = Vec2::new(p1, p2);
+        return [(Vec2::NEG_X, v), (Vec2::X, v), (Vec2::ZERO, -2.0 * v)];
+    }
+
+    /// Gets the factor that converts normalised times to simulation times
+    fn time_scale(g: f32, mass: f32, length: f32) -> f32 {
+        return (length.powi(3) / (g * mass)).sqrt();
+    }
+
+    /// Creates the three bodies in the simulation's units
+    ///
+    /// ### Arguments
+    /// - `g` The gravitational constant
+    /// - `mass` The mass of each body
+    /// - `length` The simulation length of one normalised unit
+    /// - `radius` The radius of each body
+    pub fn particles(&self, g: f32, mass: f32, length: f32, radius: f32) -> Vec<Particle> {
+        let speed = length / Self::time_scale(g, mass, length);
+        return self
+            .normalised()
+            .iter()
+            .map(|(pos, vel)| {
+                let mut p = Particle::default();
+                p.set_pos(*pos * length);
+                p.set_vel(*vel * speed);
+                // Negative radii are clamped so the setter can't fail
+                p.set_radius(radius.max(0.0)).unwrap();
+                p.set_mass_with_density(mass);
+                p
+            })
+            .collect();
+    }
+
+    /// Gets the period in the simulation's units, see `Preset::particles` for the arguments
+    pub fn scaled_period(&self, g: f32, mass: f32, length: f32) -> f32 {
+        return self.period() * Self::time_scale(g, mass, length);
+    }
+}
+
+/// A preset and the scale it is loaded at
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PresetParams {
+    pub preset: Preset,
+    /// Simulation length of one normalised unit
+    pub size: f32,
+    /// Mass of each body
+    pub mass: f32,
+}
+
+impl Default for PresetParams {
+    fn default() -> Self {
+        Self {
+            preset: Preset::default(),
+            size: 100.0,
+            mass: 1e6,
+        }
+    }
+}
+
+impl PresetParams {
+    /// Creates a scenario of the preset, with softening turned off so the orbits stay periodic
+    ///
+    /// ### Arguments
+    /// - `g` The gravitational constant the velocities are calculated for
+    pub fn scenario(&self, g: f32) -> Scenario {
+        let size = self.size.max(1.0);
+        let particles = self.preset.particles(g, self.mass, size, size * 0.03);
+        let mut scenario = Scenario::from_particles(self.preset.label(), &particles);
+        scenario.constants = ScenarioConstants {
+            softening: Some(0.0),
+            ..Default::default()
+        };
+        for (body, color) in scenario.bodies.iter_mut().zip(COLORS) {
+            body.color = Some(color);
+        }
+        return scenario;
+    }
+}
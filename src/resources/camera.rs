@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+/// Smallest projection scale (most zoomed in)
+pub const MIN_ZOOM: f32 = 0.02;
+/// Largest projection scale (most zoomed out)
+pub const MAX_ZOOM: f32 = 50.0;
+
+/// What the camera keeps in the centre of the window
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CameraFollow {
+    /// The camera stays where it was panned to
+    #[default]
+    Free,
+    /// The centre of mass of every particle
+    CenterOfMass,
+    /// A single particle, the camera is freed if it is removed (e.g. merged)
+    Particle(Entity),
+}
+
+impl CameraFollow {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraFollow::Free => "Free",
+            CameraFollow::CenterOfMass => "Centre of Mass",
+            CameraFollow::Particle(_) => "Particle",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CameraControls {
+    pub follow: CameraFollow,
+}
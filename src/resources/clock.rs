@@ -0,0 +1,187 @@
+/// Smallest time scale the controls allow
+pub const MIN_TIME_SCALE: f32 = 1.0 / 64.0;
+/// Largest time scale the controls allow, only reached if `max_steps_per_frame` is high enough for
+/// the frame rate (see `SimulationClock::dropped_fraction`)
+pub const MAX_TIME_SCALE: f32 = 16.0;
+
+/// Fixed timestep clock that decides how many physics steps to run each frame
+#[cfg_attr(feature = "app", derive(bevy::prelude::Resource))]
+pub struct SimulationClock {
+    /// The physics timestep, in seconds
+    pub dt: f32,
+    /// The number of substeps each physics step is divided into
+    pub substeps: usize,
+    /// The maximum number of physics steps run in a single frame, any time beyond this is dropped
+    pub max_steps_per_frame: usize,
+    /// When paused, physics only advances through `step_once`
+    pub paused: bool,
+    /// Simulated seconds per real second, below 1 for slow motion
+    pub time_scale: f32,
+    /// Real time the last physics step took, in seconds (measured by the app)
+    pub step_time: f32,
+    accumulator: f32,
+    pending_steps: usize,
+    last_advance: usize,
+    /// How many of the steps from the last call to `advance` have been started
+    started: usize,
+    /// Simulated time asked for by the last call to `advance`, and how much of it was dropped
+    last_requested: f32,
+    last_dropped: f32,
+    steps: u64,
+    elapsed: f64,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        Self {
+            dt: 1.0 / 120.0,
+            substeps: 1,
+            max_steps_per_frame: 8,
+            paused: false,
+            time_scale: 1.0,
+            step_time: 0.0,
+            accumulator: 0.0,
+            pending_steps: 0,
+            last_advance: 0,
+            started: 0,
+            last_requested: 0.0,
+            last_dropped: 0.0,
+            steps: 0,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Adds the time elapsed in a frame, multiplied by the time scale, to the accumulator
+    ///
+    /// While paused no time is accumulated and only steps requested with `step_once` are run
+    ///
+    /// ### Arguments
+    /// - `frame_dt` The real time elapsed since the last frame
+    ///
+    /// ### Returns
+    /// `usize` The number of physics steps to run this frame
+    pub fn advance(&mut self, frame_dt: f32) -> usize {
+        let steps = self.count_steps(frame_dt);
+        self.steps += steps as u64;
+        self.elapsed += steps as f64 * self.dt as f64;
+        self.last_advance = steps;
+        self.started = 0;
+        return steps;
+    }
+
+    /// Marks the start of the next of the steps from the last call to `advance`
+    ///
+    /// `advance` counts all of a frame's steps at once, this lets systems that run every step tell
+    /// which one they are in (see `current_step`)
+    pub fn start_step(&mut self) {
+        self.started = (self.started + 1).min(self.last_advance);
+    }
+
+    /// Gets the number of the step being run (counting from 1), and the simulated time at its end
+    ///
+    /// Between frames this is the last step run, the same as `steps` and `elapsed`
+    pub fn current_step(&self) -> (u64, f64) {
+        let remaining = (self.last_advance - self.started) as u64;
+        if remaining == 0 {
+            return (self.steps, self.elapsed);
+        }
+        return (
+            self.steps.saturating_sub(remaining),
+            self.elapsed - remaining as f64 * self.dt as f64,
+        );
+    }
+
+    /// Takes the number of whole steps due this frame out of the accumulator (or the pending steps)
+    fn count_steps(&mut self, frame_dt: f32) -> usize {
+        self.last_requested = 0.0;
+        self.last_dropped = 0.0;
+        if self.dt <= 0.0 {
+            return 0;
+        }
+        if self.paused {
+            let steps = self.pending_steps.min(self.max_steps_per_frame);
+            self.pending_steps -= steps;
+            return steps;
+        }
+        self.last_requested = frame_dt * self.time_scale.max(0.0);
+        self.accumulator += self.last_requested;
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps_per_frame {
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        if steps == self.max_steps_per_frame {
+            // Drop the backlog rather than trying to catch up on the next frame
+            let kept = self.accumulator % self.dt;
+            self.last_dropped = self.accumulator - kept;
+            self.accumulator = kept;
+        }
+        return steps;
+    }
+
+    /// Pauses the clock and runs a single physics step on the next frame
+    pub fn step_once(&mut self) {
+        self.paused = true;
+        self.pending_steps += 1;
+    }
+
+    /// Pauses a running clock, or resumes a paused one
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    /// Restarts the clock from zero, keeping the timestep settings
+    pub fn reset(&mut self) {
+        self.restore(0, 0.0);
+    }
+
+    /// Continues the clock from a previous run, keeping the timestep settings
+    ///
+    /// ### Arguments
+    /// - `steps` The number of physics steps already run
+    /// - `elapsed` The simulated time already elapsed, in seconds
+    pub fn restore(&mut self, steps: u64, elapsed: f64) {
+        self.accumulator = 0.0;
+        self.pending_steps = 0;
+        self.steps = steps;
+        self.elapsed = elapsed;
+    }
+
+    /// Gets the timestep of a single substep
+    pub fn substep_dt(&self) -> f32 {
+        return self.dt / (self.substeps.max(1) as f32);
+    }
+
+    /// Gets the number of physics steps run so far
+    pub fn steps(&self) -> u64 {
+        return self.steps;
+    }
+
+    /// Gets the number of physics steps run by the last call to `advance`
+    pub fn last_advance(&self) -> usize {
+        return self.last_advance;
+    }
+
+    /// Gets the fraction of the simulated time asked for by the last call to `advance` that was
+    /// dropped because of `max_steps_per_frame`
+    ///
+    /// Above 0 the simulation runs slower than the time scale
+    pub fn dropped_fraction(&self) -> f32 {
+        if self.last_requested <= 0.0 {
+            return 0.0;
+        }
+        return (self.last_dropped / self.last_requested).min(1.0);
+    }
+
+    /// Gets the simulated time elapsed so far, in seconds
+    pub fn elapsed(&self) -> f64 {
+        return self.elapsed;
+    }
+}
@@ -1,5 +1,6 @@
 use std::ops::RangeInclusive;
 
+#[derive(Clone)]
 pub struct NumericConstant {
     pub value: f32,
     pub range: RangeInclusive<f32>,
@@ -18,9 +19,18 @@ impl NumericConstant {
     }
 }
 
+#[derive(Clone)]
 pub struct NumericConstants {
     pub g: NumericConstant,
     pub restitution: NumericConstant,
+    pub theta: NumericConstant,
+    pub softening: NumericConstant,
+}
+
+impl Default for NumericConstants {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NumericConstants {
@@ -28,13 +38,17 @@ impl NumericConstants {
         return Self {
             g: NumericConstant::new(6.7, 0.0..=100.0, 0.1, "Gravitational Force Constant"),
             restitution: NumericConstant::new(0.8, 0.0..=1.0, 0.01, "Elastic Restitution"),
+            theta: NumericConstant::new(0.5, 0.0..=2.0, 0.01, "Barnes-Hut Opening Angle"),
+            softening: NumericConstant::new(1.0, 0.0..=50.0, 0.1, "Softening Length"),
         };
     }
 
     pub fn to_vec_mut(&mut self) -> Vec<&mut NumericConstant> {
-        let mut vec = vec![];
-        vec.push(&mut self.g);
-        vec.push(&mut self.restitution);
-        return vec;
+        return vec![
+            &mut self.g,
+            &mut self.restitution,
+            &mut self.theta,
+            &mut self.softening,
+        ];
     }
 }
@@ -1,8 +1,39 @@
+use crate::colormap::{ColorMode, Colormap};
+use crate::particle::path;
 use bevy::prelude::*;
 
-#[derive(Default)]
 pub struct Controls {
     pub show_path: bool,
+    /// Number of points kept in each particle's trail
+    pub trail_length: usize,
+    pub show_velocity: bool,
+    pub show_acceleration: bool,
+    /// Length of the velocity arrows per unit of speed
+    pub velocity_scale: f32,
+    /// Length of the acceleration arrows per unit of acceleration
+    pub acceleration_scale: f32,
+    /// The colour new particles are created with
     pub particle_color: Color,
+    /// The outline of particles that aren't selected
     pub particle_stroke: Color,
+    pub color_mode: ColorMode,
+    /// Used by the continuous colour modes
+    pub colormap: Colormap,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            show_path: false,
+            trail_length: path::DEFAULT_CAPACITY,
+            show_velocity: false,
+            show_acceleration: false,
+            velocity_scale: 0.5,
+            acceleration_scale: 0.1,
+            particle_color: Color::WHITE,
+            particle_stroke: Color::rgba(0., 0., 0., 0.),
+            color_mode: ColorMode::default(),
+            colormap: Colormap::default(),
+        }
+    }
 }
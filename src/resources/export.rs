@@ -0,0 +1,40 @@
+use crate::export::TrajectoryWriter;
+use bevy::prelude::*;
+
+/// The ID the next new particle is given, IDs are never reused
+#[derive(Resource, Default)]
+pub struct NextParticleId(pub u64);
+
+/// What to do with the trajectory recording on the next frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportRequest {
+    Start,
+    Stop,
+}
+
+/// The file trajectories are recorded to
+#[derive(Resource)]
+pub struct TrajectoryExport {
+    /// Path to a `.csv`, `.npy` or `.npz` file
+    pub path: String,
+    /// Physics steps between records, a record is taken after every step whose number is a
+    /// multiple of this
+    pub every: u64,
+    /// The recording in progress, if there is one
+    pub writer: Option<TrajectoryWriter>,
+    /// Why the last recording failed, if it did
+    pub error: Option<String>,
+    pub request: Option<ExportRequest>,
+}
+
+impl Default for TrajectoryExport {
+    fn default() -> Self {
+        Self {
+            path: "trajectory.csv".to_string(),
+            every: 10,
+            writer: None,
+            error: None,
+            request: None,
+        }
+    }
+}
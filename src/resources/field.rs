@@ -0,0 +1,38 @@
+use crate::colormap::Colormap;
+use crate::physics::field::FieldQuantity;
+use bevy::prelude::*;
+
+/// Settings of the background layer showing the gravitational field over the visible area
+#[derive(Resource)]
+pub struct FieldOverlay {
+    pub enabled: bool,
+    pub quantity: FieldQuantity,
+    pub colormap: Colormap,
+    /// Opacity of the heatmap, from 0 to 1
+    pub opacity: f32,
+    pub contours: bool,
+    /// Number of contour lines
+    pub contour_levels: usize,
+    /// Arrows showing the direction of the field
+    pub arrows: bool,
+    /// Samples across the longer side of the window
+    pub resolution: usize,
+    /// Updates per second
+    pub rate: f32,
+}
+
+impl Default for FieldOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quantity: FieldQuantity::default(),
+            colormap: Colormap::Magma,
+            opacity: 0.7,
+            contours: true,
+            contour_levels: 12,
+            arrows: false,
+            resolution: 128,
+            rate: 5.0,
+        }
+    }
+}
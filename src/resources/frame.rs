@@ -0,0 +1,44 @@
+use crate::particle::path::Path;
+use bevy::prelude::*;
+
+/// The reference frame trails are drawn in
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ReferenceFrame {
+    #[default]
+    Inertial,
+    /// Moving with the centre of mass of every particle
+    CenterOfMass,
+    /// Moving with a single particle
+    Particle(Entity),
+    /// Centred on, and rotating with, a pair of particles (e.g. a binary or a star and planet)
+    CoRotating(Entity, Entity),
+}
+
+impl ReferenceFrame {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReferenceFrame::Inertial => "Inertial",
+            ReferenceFrame::CenterOfMass => "Centre of Mass",
+            ReferenceFrame::Particle(_) => "Particle",
+            ReferenceFrame::CoRotating(_, _) => "Co-rotating Pair",
+        }
+    }
+}
+
+/// The frame trails are drawn in, trails are always recorded in the inertial frame and are
+/// re-projected when drawn, so switching frames keeps their history
+#[derive(Resource)]
+pub struct TrailFrame {
+    pub frame: ReferenceFrame,
+    /// Recorded alongside the trails, so trails can be drawn relative to it
+    pub center_of_mass: Path,
+}
+
+impl Default for TrailFrame {
+    fn default() -> Self {
+        Self {
+            frame: ReferenceFrame::default(),
+            center_of_mass: Path::new(crate::particle::path::DEFAULT_CAPACITY),
+        }
+    }
+}
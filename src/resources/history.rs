@@ -0,0 +1,31 @@
+use crate::history::History;
+use bevy::prelude::*;
+
+/// Simulated seconds of history kept by default
+pub const DEFAULT_WINDOW: f64 = 60.0;
+
+/// The recorded history of the simulation's totals, and the window plotting it
+#[derive(Resource)]
+pub struct HistoryPlot {
+    pub history: History,
+    /// Whether the plot window is shown
+    pub open: bool,
+    /// Path the history is exported to as CSV
+    pub path: String,
+    /// Why the last export failed, if it did
+    pub error: Option<String>,
+    /// Export on the next frame
+    pub export: bool,
+}
+
+impl Default for HistoryPlot {
+    fn default() -> Self {
+        Self {
+            history: History::new(DEFAULT_WINDOW),
+            open: false,
+            path: "history.csv".to_string(),
+            error: None,
+            export: false,
+        }
+    }
+}
@@ -1,24 +1,52 @@
-use bevy::prelude::*;
+#[cfg(feature = "app")]
+pub mod camera;
+pub mod clock;
 pub mod constants;
+#[cfg(feature = "app")]
 pub mod controls;
+#[cfg(feature = "app")]
+pub mod export;
+#[cfg(feature = "app")]
+pub mod field;
+#[cfg(feature = "app")]
+pub mod frame;
+#[cfg(feature = "app")]
+pub mod history;
+#[cfg(feature = "app")]
 pub mod input;
+#[cfg(feature = "app")]
+pub mod scenario;
+#[cfg(feature = "app")]
+pub mod selection;
+#[cfg(feature = "app")]
+pub mod snapshot;
+#[cfg(feature = "app")]
+pub mod spawn;
+
+#[cfg(feature = "app")]
+use crate::simulation;
+#[cfg(feature = "app")]
+use bevy::prelude::*;
 
+#[cfg(feature = "app")]
 #[derive(Resource)]
 pub struct SimulationState {
-    pub numeric_constants: constants::NumericConstants,
+    pub settings: simulation::Settings,
     pub controls: controls::Controls,
 }
 
+#[cfg(feature = "app")]
 impl Default for SimulationState {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "app")]
 impl SimulationState {
     pub fn new() -> Self {
         Self {
-            numeric_constants: constants::NumericConstants::new(),
+            settings: simulation::Settings::new(),
             controls: Default::default(),
         }
     }
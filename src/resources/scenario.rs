@@ -0,0 +1,44 @@
+use crate::generators::GeneratorParams;
+use crate::presets::PresetParams;
+use bevy::prelude::*;
+
+/// What to replace the simulation with on the next frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScenarioRequest {
+    /// Load (or reload) the scenario file
+    File,
+    /// The generator's particles
+    Generator,
+    /// The selected preset
+    Preset,
+}
+
+/// The scenario file the simulation is loaded from
+#[derive(Resource, Default)]
+pub struct ScenarioFile {
+    /// Path to a `.ron` or `.json` scenario, empty for the built-in scenario
+    pub path: String,
+    /// Why the last load failed, if it did
+    pub error: Option<String>,
+    pub request: Option<ScenarioRequest>,
+    /// The generator selected in the GUI
+    pub generator: GeneratorParams,
+    /// The preset selected in the GUI
+    pub preset: PresetParams,
+}
+
+impl ScenarioFile {
+    /// Creates a scenario file that is loaded on the first frame
+    ///
+    /// ### Arguments
+    /// - `path` The scenario to load, `None` for the built-in scenario
+    pub fn new(path: Option<String>) -> Self {
+        return Self {
+            path: path.unwrap_or_default(),
+            error: None,
+            request: Some(ScenarioRequest::File),
+            generator: GeneratorParams::default(),
+            preset: PresetParams::default(),
+        };
+    }
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+/// Which property is kept fixed when the mass of a particle is edited
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MassEdit {
+    /// Changes the density (`Particle::set_mass_with_density`)
+    #[default]
+    KeepRadius,
+    /// Changes the radius (`Particle::set_mass_with_radius`)
+    KeepDensity,
+}
+
+/// The particle shown in the inspector
+#[derive(Resource, Default)]
+pub struct Selection {
+    pub entity: Option<Entity>,
+    pub mass_edit: MassEdit,
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+/// What to do with the snapshot file on the next frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotRequest {
+    Save,
+    Load,
+}
+
+/// The file simulation snapshots are saved to and loaded from
+#[derive(Resource)]
+pub struct SnapshotFile {
+    /// Path to a `.ron` or `.json` snapshot
+    pub path: String,
+    /// Why the last save or load failed, if it did
+    pub error: Option<String>,
+    pub request: Option<SnapshotRequest>,
+}
+
+impl Default for SnapshotFile {
+    fn default() -> Self {
+        Self {
+            path: "snapshot.ron".to_string(),
+            error: None,
+            request: None,
+        }
+    }
+}
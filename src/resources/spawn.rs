@@ -0,0 +1,50 @@
+use crate::physics::orbit::OrbitTarget;
+use bevy::prelude::*;
+
+/// How clicking in the window spawns particles
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SpawnTool {
+    /// Drag to set the launch velocity
+    #[default]
+    Launch,
+    /// Click to place a particle on an orbit
+    Orbit,
+}
+
+impl SpawnTool {
+    pub const ALL: [SpawnTool; 2] = [SpawnTool::Launch, SpawnTool::Orbit];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpawnTool::Launch => "Drag to Launch",
+            SpawnTool::Orbit => "Click to Orbit",
+        }
+    }
+}
+
+/// The particles spawned with the mouse
+#[derive(Resource)]
+pub struct SpawnSettings {
+    pub tool: SpawnTool,
+    pub radius: f32,
+    pub density: f32,
+    /// The body orbited with `SpawnTool::Orbit`
+    pub target: OrbitTarget,
+    /// Eccentricity of orbits, the particle is placed at the periapsis
+    pub eccentricity: f32,
+    /// Orbit anti-clockwise, holding shift while clicking reverses it
+    pub prograde: bool,
+}
+
+impl Default for SpawnSettings {
+    fn default() -> Self {
+        Self {
+            tool: SpawnTool::default(),
+            radius: 5.0,
+            density: 0.1,
+            target: OrbitTarget::default(),
+            eccentricity: 0.0,
+            prograde: true,
+        }
+    }
+}
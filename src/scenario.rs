@@ -0,0 +1,206 @@
+use crate::particle::Particle;
+use crate::resources::constants::{NumericConstant, NumericConstants};
+use anyhow::{anyhow, Context, Result};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A set of bodies and the constants they are simulated with, stored as RON or JSON
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub constants: ScenarioConstants,
+    pub bodies: Vec<Body>,
+}
+
+/// Simulation constants set by a scenario
+///
+/// Scenario files are applied over the default constants, so any that are left out are reset to
+/// their defaults. Generators and presets are applied over the current constants instead
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioConstants {
+    pub g: Option<f32>,
+    pub restitution: Option<f32>,
+    pub theta: Option<f32>,
+    pub softening: Option<f32>,
+}
+
+/// A single body in a scenario
+///
+/// Exactly one of `density` or `mass` must be given
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Body {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub position: Vec2,
+    #[serde(default)]
+    pub velocity: Vec2,
+    pub radius: f32,
+    #[serde(default)]
+    pub density: Option<f32>,
+    #[serde(default)]
+    pub mass: Option<f32>,
+    /// RGBA, each component from 0 to 1
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+}
+
+impl Scenario {
+    /// Parses and validates a scenario written in RON
+    ///
+    /// Optional fields can be written without `Some(..)`
+    pub fn from_ron(text: &str) -> Result<Self> {
+        let options = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+        let scenario: Self = options.from_str(text)?;
+        scenario.validate()?;
+        return Ok(scenario);
+    }
+
+    /// Parses and validates a scenario written in JSON
+    pub fn from_json(text: &str) -> Result<Self> {
+        let scenario: Self = serde_json::from_str(text)?;
+        scenario.validate()?;
+        return Ok(scenario);
+    }
+
+    /// Loads and validates a scenario, the format is chosen by the file extension (`.ron` or `.json`)
+    ///
+    /// ### Arguments
+    /// - `path` The scenario file
+    ///
+    /// ### Returns
+    /// `Result<Scenario>` The scenario, or an error describing what is wrong with the file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read '{}'", path.display()))?;
+        let scenario = match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => Self::from_ron(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(anyhow!("expected a .ron or .json file")),
+        };
+        return scenario.with_context(|| format!("invalid scenario '{}'", path.display()));
+    }
+
+    /// Creates a scenario from existing particles, e.g. ones made by a generator
+    ///
+    /// ### Arguments
+    /// - `name` The name of the scenario
+    /// - `particles` The particles, each becomes a body with its radius and density
+    pub fn from_particles(name: &str, particles: &[Particle]) -> Self {
+        return Self {
+            name: name.to_string(),
+            constants: ScenarioConstants::default(),
+            bodies: particles
+                .iter()
+                .map(|p| Body {
+                    name: None,
+                    position: p.position(),
+                    velocity: p.velocity(),
+                    radius: p.radius(),
+                    density: Some(p.density()),
+                    mass: None,
+                    color: None,
+                })
+                .collect(),
+        };
+    }
+
+    /// Checks that every body and constant is valid
+    pub fn validate(&self) -> Result<()> {
+        self.particles()?;
+        self.constants.apply(&mut NumericConstants::new())?;
+        return Ok(());
+    }
+
+    /// Creates a particle for every body
+    pub fn particles(&self) -> Result<Vec<Particle>> {
+        return self
+            .bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                body.particle().with_context(|| match &body.name {
+                    Some(name) => format!("body {} ('{}')", i, name),
+                    None => format!("body {}", i),
+                })
+            })
+            .collect();
+    }
+}
+
+impl ScenarioConstants {
+    /// Sets the constants given by the scenario, the others are left as they are
+    ///
+    /// Nothing is changed if any of the values are outside of their allowed range
+    ///
+    /// ### Arguments
+    /// - `constants` The constants to update
+    pub fn apply(&self, constants: &mut NumericConstants) -> Result<()> {
+        let mut updated = constants.clone();
+        set_constant(&mut updated.g, self.g, "g")?;
+        set_constant(&mut updated.restitution, self.restitution, "restitution")?;
+        set_constant(&mut updated.theta, self.theta, "theta")?;
+        set_constant(&mut updated.softening, self.softening, "softening")?;
+        *constants = updated;
+        return Ok(());
+    }
+}
+
+fn set_constant(constant: &mut NumericConstant, value: Option<f32>, name: &str) -> Result<()> {
+    if let Some(value) = value {
+        if !constant.range.contains(&value) {
+            return Err(anyhow!(
+                "constant '{}' = {} is outside of the allowed range {:?}",
+                name,
+                value,
+                constant.range
+            ));
+        }
+        constant.value = value;
+    }
+    return Ok(());
+}
+
+impl Body {
+    /// Creates the particle described by the body
+    pub fn particle(&self) -> Result<Particle> {
+        if !self.position.is_finite() || !self.velocity.is_finite() {
+            return Err(anyhow!("position and velocity must be finite"));
+        }
+        if !(self.radius.is_finite() && self.radius > 0.0) {
+            return Err(anyhow!(
+                "radius must be greater than 0, found {}",
+                self.radius
+            ));
+        }
+        if let Some(color) = self.color {
+            if color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+                return Err(anyhow!("color components must be between 0 and 1"));
+            }
+        }
+
+        let mut p = Particle::default();
+        p.set_pos(self.position);
+        p.set_vel(self.velocity);
+        p.set_radius(self.radius)?;
+        match (self.density, self.mass) {
+            (Some(density), None) if density.is_finite() && density > 0.0 => {
+                p.set_density(density)?;
+            }
+            (None, Some(mass)) if mass.is_finite() && mass > 0.0 => {
+                p.set_mass_with_density(mass);
+            }
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(anyhow!("exactly one of 'density' or 'mass' must be given"));
+            }
+            _ => return Err(anyhow!("density and mass must be greater than 0")),
+        }
+        return Ok(p);
+    }
+}
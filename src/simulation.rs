@@ -0,0 +1,223 @@
+use crate::particle::Particle;
+use crate::physics::{self, integrator::IntegratorKind, CollisionMode, ForceSolver, Merge};
+use crate::resources::constants::NumericConstants;
+use glam::Vec2;
+
+/// Everything that controls how particles are advanced, apart from the particles themselves
+#[derive(Clone)]
+pub struct Settings {
+    pub constants: NumericConstants,
+    pub integrator: IntegratorKind,
+    pub force_solver: ForceSolver,
+    pub collision_mode: CollisionMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            constants: NumericConstants::new(),
+            integrator: IntegratorKind::default(),
+            force_solver: ForceSolver::default(),
+            collision_mode: CollisionMode::default(),
+        }
+    }
+
+    /// Gets the gravitational acceleration of every particle
+    pub fn accelerations(&self, particles: &[Particle]) -> Vec<Vec2> {
+        return physics::accelerations(
+            particles,
+            self.constants.g.value,
+            self.constants.softening.value,
+            self.force_solver,
+            self.constants.theta.value,
+        );
+    }
+
+    /// Advances particles by one timestep, resolving collisions and then integrating
+    ///
+    /// ### Arguments
+    /// - `particles` The particles to advance, absorbed particles are removed
+    /// - `dt` The timestep
+    ///
+    /// ### Returns
+    /// `Vec<Merge>` The merges that happened (always empty when bouncing)
+    pub fn step(&self, particles: &mut Vec<Particle>, dt: f32) -> Vec<Merge> {
+        let merges = match self.collision_mode {
+            CollisionMode::Bounce => {
+                physics::bounce_collisions(particles, self.constants.restitution.value);
+                Vec::new()
+            }
+            CollisionMode::Merge => physics::merge_collisions(particles),
+        };
+        self.integrator
+            .integrator()
+            .step(particles, dt, &|ps| self.accelerations(ps));
+        return merges;
+    }
+
+    /// Predicts the path of a particle that has not been added to the simulation yet
+    ///
+    /// The other particles are copied and move under gravity too, but collisions are not resolved.
+    /// The prediction stops early if the particle hits another one
+    ///
+    /// ### Arguments
+    /// - `particles` The particles already in the simulation (left untouched)
+    /// - `probe` The particle to predict the path of
+    /// - `dt` The timestep
+    /// - `steps` The maximum number of steps to predict
+    ///
+    /// ### Returns
+    /// `Vec<Vec2>` The positions of the probe, starting with its current position
+    pub fn predict(
+        &self,
+        particles: &[Particle],
+        probe: Particle,
+        dt: f32,
+        steps: usize,
+    ) -> Vec<Vec2> {
+        let mut particles = particles.to_vec();
+        particles.push(probe);
+        let last = particles.len() - 1;
+        let integrator = self.integrator.integrator();
+        let mut points = vec![particles[last].position()];
+        for _ in 0..steps {
+            integrator.step(&mut particles, dt, &|ps| self.accelerations(ps));
+            let (others, probe) = particles.split_at(last);
+            let probe = &probe[0];
+            if !probe.position().is_finite() {
+                break;
+            }
+            points.push(probe.position());
+            if others.iter().any(|p| physics::is_intersecting(probe, p)) {
+                break;
+            }
+        }
+        return points;
+    }
+}
+
+/// A self contained simulation that can be run without Bevy (e.g. in tests and batch jobs)
+#[derive(Clone)]
+pub struct Simulation {
+    pub particles: Vec<Particle>,
+    pub settings: Settings,
+    time: f64,
+    steps: u64,
+    /// The ID of each particle, in the same order as `particles`
+    ids: Vec<u64>,
+    next_id: u64,
+}
+
+impl Simulation {
+    /// Creates a simulation, the particles are given the IDs 0, 1, 2...
+    pub fn new(particles: Vec<Particle>, settings: Settings) -> Self {
+        return Self::resume(particles, settings, 0.0, 0);
+    }
+
+    /// Creates a simulation that has already been running, e.g. one restored from a snapshot
+    ///
+    /// ### Arguments
+    /// - `particles` The particles
+    /// - `settings` The settings they are simulated with
+    /// - `time` The simulated time elapsed so far
+    /// - `steps` The number of steps run so far
+    pub fn resume(particles: Vec<Particle>, settings: Settings, time: f64, steps: u64) -> Self {
+        let mut sim = Self {
+            particles,
+            settings,
+            time,
+            steps,
+            ids: Vec::new(),
+            next_id: 0,
+        };
+        sim.assign_ids();
+        return sim;
+    }
+
+    /// Gives the particles the IDs they had in a previous run, e.g. one restored from a snapshot
+    ///
+    /// ### Arguments
+    /// - `ids` The ID of each particle, in the same order as `particles`
+    /// - `next_id` The ID the next new particle is given
+    pub fn restore_ids(&mut self, ids: Vec<u64>, next_id: u64) {
+        self.ids = ids;
+        self.next_id = next_id;
+        self.assign_ids();
+    }
+
+    /// Adds a particle to the simulation
+    ///
+    /// ### Returns
+    /// `u64` The particle's ID
+    pub fn add(&mut self, particle: Particle) -> u64 {
+        self.particles.push(particle);
+        self.assign_ids();
+        return self.ids[self.ids.len() - 1];
+    }
+
+    /// Gives an ID to every particle pushed onto `particles` since IDs were last assigned
+    fn assign_ids(&mut self) {
+        self.ids.truncate(self.particles.len());
+        while self.ids.len() < self.particles.len() {
+            self.ids.push(self.next_id);
+            self.next_id += 1;
+        }
+    }
+
+    /// Gets the ID of each particle, in the same order as `particles`
+    ///
+    /// IDs are never reused: a particle that survives a merge keeps its ID, and the ID of an
+    /// absorbed particle is retired. Particles pushed straight onto `particles` (rather than with
+    /// `add`) are given IDs on the next step
+    pub fn ids(&self) -> &[u64] {
+        return &self.ids;
+    }
+
+    /// Gets the ID the next new particle is given
+    pub fn next_id(&self) -> u64 {
+        return self.next_id;
+    }
+
+    /// Advances the simulation by one timestep
+    ///
+    /// ### Arguments
+    /// - `dt` The timestep
+    ///
+    /// ### Returns
+    /// `Vec<Merge>` The merges that happened during the step
+    pub fn step(&mut self, dt: f32) -> Vec<Merge> {
+        self.assign_ids();
+        let merges = self.settings.step(&mut self.particles, dt);
+        physics::remove_absorbed(&mut self.ids, &merges);
+        self.time += dt as f64;
+        self.steps += 1;
+        return merges;
+    }
+
+    /// Advances the simulation by a number of timesteps
+    ///
+    /// ### Arguments
+    /// - `dt` The timestep
+    /// - `n` The number of steps
+    pub fn run(&mut self, dt: f32, n: usize) {
+        for _ in 0..n {
+            self.step(dt);
+        }
+    }
+
+    /// Gets the simulated time elapsed so far
+    pub fn time(&self) -> f64 {
+        return self.time;
+    }
+
+    /// Gets the number of steps run so far
+    pub fn steps(&self) -> u64 {
+        return self.steps;
+    }
+}
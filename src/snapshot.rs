@@ -0,0 +1,450 @@
+use crate::colormap::{ColorMode, Colormap};
+use crate::particle::path::Path as ParticlePath;
+use crate::particle::Particle;
+use crate::resources::clock::SimulationClock;
+#[cfg(feature = "app")]
+use crate::resources::controls::Controls;
+use crate::scenario::ScenarioConstants;
+use crate::simulation::{Settings, Simulation};
+use anyhow::{anyhow, Context, Result};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// The snapshot format version written by this build, files with any other version are rejected
+pub const VERSION: u32 = 1;
+
+/// The full state of a simulation, stored as RON or JSON so it can be restored later
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Simulated time elapsed, in seconds
+    pub time: f64,
+    /// Number of physics steps run
+    pub steps: u64,
+    pub constants: ScenarioConstants,
+    /// Short names of the integrator, force solver and collision mode
+    pub integrator: String,
+    pub force_solver: String,
+    pub collision_mode: String,
+    /// The app's clock settings, not present in headless snapshots
+    #[serde(default)]
+    pub clock: Option<ClockState>,
+    /// The app's display controls, not present in headless snapshots
+    #[serde(default)]
+    pub controls: Option<ControlsState>,
+    /// The app's centre of mass trail, which trails can be drawn relative to
+    pub center_of_mass_path: Vec<Vec2>,
+    /// The ID the next new particle is given
+    pub next_id: u64,
+    pub particles: Vec<ParticleState>,
+}
+
+/// Settings of the app's simulation clock
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ClockState {
+    pub dt: f32,
+    pub substeps: usize,
+    pub paused: bool,
+    pub time_scale: f32,
+    pub max_steps_per_frame: usize,
+}
+
+/// Display controls of the app, colours are RGBA with each component from 0 to 1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ControlsState {
+    pub show_path: bool,
+    pub particle_color: [f32; 4],
+    pub particle_stroke: [f32; 4],
+    pub trail_length: usize,
+    pub show_velocity: bool,
+    pub show_acceleration: bool,
+    pub velocity_scale: f32,
+    pub acceleration_scale: f32,
+    /// Short names of the colour mode and colormap
+    pub color_mode: String,
+    pub colormap: String,
+}
+
+/// A single particle and the path it has traced
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParticleState {
+    /// Particles with an ID already taken by an earlier particle are given new ones when restored
+    pub id: u64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub acceleration: Vec2,
+    pub radius: f32,
+    pub density: f32,
+    pub path: Vec<Vec2>,
+    pub path_capacity: usize,
+    /// The colour the app created the particle with, RGBA
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+}
+
+/// Only the version, read before the rest of the file so old files get a clear error
+#[derive(Deserialize)]
+struct Versioned {
+    version: u32,
+}
+
+impl Snapshot {
+    /// Captures particles and settings, paths are left empty and particles are numbered in order
+    ///
+    /// ### Arguments
+    /// - `particles` The particles to save
+    /// - `settings` The settings they are simulated with
+    /// - `time` The simulated time elapsed
+    /// - `steps` The number of steps run
+    pub fn new(particles: &[Particle], settings: &Settings, time: f64, steps: u64) -> Self {
+        let constants = &settings.constants;
+        return Self {
+            version: VERSION,
+            time,
+            steps,
+            constants: ScenarioConstants {
+                g: Some(constants.g.value),
+                restitution: Some(constants.restitution.value),
+                theta: Some(constants.theta.value),
+                softening: Some(constants.softening.value),
+            },
+            integrator: settings.integrator.name().to_string(),
+            force_solver: settings.force_solver.name().to_string(),
+            collision_mode: settings.collision_mode.name().to_string(),
+            clock: None,
+            controls: None,
+            center_of_mass_path: Vec::new(),
+            next_id: particles.len() as u64,
+            particles: particles
+                .iter()
+                .enumerate()
+                .map(|(id, p)| ParticleState::new(id as u64, p))
+                .collect(),
+        };
+    }
+
+    /// Captures a headless simulation, along with the IDs of its particles
+    pub fn from_simulation(sim: &Simulation) -> Self {
+        let mut snapshot = Self::new(&sim.particles, &sim.settings, sim.time(), sim.steps());
+        for (p, id) in snapshot.particles.iter_mut().zip(sim.ids()) {
+            p.id = *id;
+        }
+        snapshot.next_id = sim.next_id();
+        return snapshot;
+    }
+
+    /// Restores a headless simulation that continues exactly where the snapshot was taken
+    pub fn simulation(&self) -> Result<Simulation> {
+        let mut sim =
+            Simulation::resume(self.particles()?, self.settings()?, self.time, self.steps);
+        let (ids, next_id) = self.ids();
+        sim.restore_ids(ids, next_id);
+        return Ok(sim);
+    }
+
+    /// Gets the ID of every saved particle, and the ID the next new particle is given
+    ///
+    /// Particles with an ID already taken by an earlier particle are given new IDs. No ID at or
+    /// above the returned next ID is in use, so retired IDs are not reused
+    pub fn ids(&self) -> (Vec<u64>, u64) {
+        let mut next_id = self
+            .particles
+            .iter()
+            .map(|p| p.id + 1)
+            .fold(self.next_id, u64::max);
+        let mut used = HashSet::new();
+        let ids = self
+            .particles
+            .iter()
+            .map(|p| {
+                if used.insert(p.id) {
+                    return p.id;
+                }
+                next_id += 1;
+                return next_id - 1;
+            })
+            .collect();
+        return (ids, next_id);
+    }
+
+    /// Gets the settings saved in the snapshot, starting from the defaults for anything left out
+    pub fn settings(&self) -> Result<Settings> {
+        let mut settings = Settings::new();
+        self.constants.apply(&mut settings.constants)?;
+        settings.integrator = self.integrator.parse().context("integrator")?;
+        settings.force_solver = self.force_solver.parse().context("force_solver")?;
+        settings.collision_mode = self.collision_mode.parse().context("collision_mode")?;
+        return Ok(settings);
+    }
+
+    /// Creates a particle for every saved particle
+    pub fn particles(&self) -> Result<Vec<Particle>> {
+        return self
+            .particles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p.particle().with_context(|| format!("particle {}", i)))
+            .collect();
+    }
+
+    /// Checks the snapshot can be restored
+    pub fn validate(&self) -> Result<()> {
+        if self.version != VERSION {
+            return Err(version_error(self.version));
+        }
+        if !self.time.is_finite() {
+            return Err(anyhow!("time must be finite"));
+        }
+        if let Some(clock) = &self.clock {
+            clock.validate().context("clock")?;
+        }
+        if let Some(controls) = &self.controls {
+            controls.validate().context("controls")?;
+        }
+        self.settings()?;
+        self.particles()?;
+        return Ok(());
+    }
+
+    /// Parses and validates a snapshot written in RON
+    pub fn from_ron(text: &str) -> Result<Self> {
+        let versioned: Versioned = ron::from_str(text)?;
+        if versioned.version != VERSION {
+            return Err(version_error(versioned.version));
+        }
+        let snapshot: Self = ron::from_str(text)?;
+        snapshot.validate()?;
+        return Ok(snapshot);
+    }
+
+    /// Parses and validates a snapshot written in JSON
+    pub fn from_json(text: &str) -> Result<Self> {
+        let versioned: Versioned = serde_json::from_str(text)?;
+        if versioned.version != VERSION {
+            return Err(version_error(versioned.version));
+        }
+        let snapshot: Self = serde_json::from_str(text)?;
+        snapshot.validate()?;
+        return Ok(snapshot);
+    }
+
+    /// Writes the snapshot as RON
+    pub fn to_ron(&self) -> Result<String> {
+        return Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?);
+    }
+
+    /// Writes the snapshot as JSON
+    pub fn to_json(&self) -> Result<String> {
+        return Ok(serde_json::to_string_pretty(self)?);
+    }
+
+    /// Loads and validates a snapshot, the format is chosen by the file extension (`.ron` or `.json`)
+    ///
+    /// ### Arguments
+    /// - `path` The snapshot file
+    ///
+    /// ### Returns
+    /// `Result<Snapshot>` The snapshot, or an error describing what is wrong with the file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read '{}'", path.display()))?;
+        let snapshot = match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => Self::from_ron(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(anyhow!("expected a .ron or .json file")),
+        };
+        return snapshot.with_context(|| format!("invalid snapshot '{}'", path.display()));
+    }
+
+    /// Saves the snapshot, the format is chosen by the file extension (`.ron` or `.json`)
+    ///
+    /// ### Arguments
+    /// - `path` The file to write
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => self.to_ron()?,
+            Some("json") => self.to_json()?,
+            _ => return Err(anyhow!("expected a .ron or .json file")),
+        };
+        std::fs::write(path, text)
+            .with_context(|| format!("could not write '{}'", path.display()))?;
+        return Ok(());
+    }
+}
+
+impl ClockState {
+    /// Captures the clock's settings
+    pub fn new(clock: &SimulationClock) -> Self {
+        return Self {
+            dt: clock.dt,
+            substeps: clock.substeps,
+            paused: clock.paused,
+            time_scale: clock.time_scale,
+            max_steps_per_frame: clock.max_steps_per_frame,
+        };
+    }
+
+    /// Checks the settings can be applied
+    pub fn validate(&self) -> Result<()> {
+        if !(self.dt.is_finite() && self.dt > 0.0) || self.substeps == 0 {
+            return Err(anyhow!("dt and substeps must be greater than 0"));
+        }
+        if !(self.time_scale.is_finite() && self.time_scale > 0.0) {
+            return Err(anyhow!("time_scale must be greater than 0"));
+        }
+        if self.max_steps_per_frame == 0 {
+            return Err(anyhow!("max_steps_per_frame must be greater than 0"));
+        }
+        return Ok(());
+    }
+
+    /// Sets the saved settings on a clock, the steps and elapsed time are left untouched
+    pub fn apply(&self, clock: &mut SimulationClock) {
+        clock.dt = self.dt;
+        clock.substeps = self.substeps;
+        clock.paused = self.paused;
+        clock.time_scale = self.time_scale;
+        clock.max_steps_per_frame = self.max_steps_per_frame;
+    }
+}
+
+impl ControlsState {
+    /// Checks the controls can be applied
+    pub fn validate(&self) -> Result<()> {
+        if self.trail_length == 0 {
+            return Err(anyhow!("trail_length must be greater than 0"));
+        }
+        for scale in [self.velocity_scale, self.acceleration_scale] {
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err(anyhow!("arrow scales must be greater than 0"));
+            }
+        }
+        self.color_mode()?;
+        self.colormap()?;
+        return Ok(());
+    }
+
+    /// Gets the saved colour mode
+    pub fn color_mode(&self) -> Result<ColorMode> {
+        return self.color_mode.parse().context("color_mode");
+    }
+
+    /// Gets the saved colormap
+    pub fn colormap(&self) -> Result<Colormap> {
+        return self.colormap.parse().context("colormap");
+    }
+}
+
+#[cfg(feature = "app")]
+impl ControlsState {
+    /// Captures the app's display controls
+    pub fn new(controls: &Controls) -> Self {
+        return Self {
+            show_path: controls.show_path,
+            particle_color: controls.particle_color.as_rgba_f32(),
+            particle_stroke: controls.particle_stroke.as_rgba_f32(),
+            trail_length: controls.trail_length,
+            show_velocity: controls.show_velocity,
+            show_acceleration: controls.show_acceleration,
+            velocity_scale: controls.velocity_scale,
+            acceleration_scale: controls.acceleration_scale,
+            color_mode: controls.color_mode.name().to_string(),
+            colormap: controls.colormap.name().to_string(),
+        };
+    }
+
+    /// Sets the saved controls
+    ///
+    /// Nothing is changed if any of the controls are invalid
+    pub fn apply(&self, controls: &mut Controls) -> Result<()> {
+        let color_mode = self.color_mode()?;
+        let colormap = self.colormap()?;
+        let color = |[r, g, b, a]: [f32; 4]| bevy::prelude::Color::rgba(r, g, b, a);
+        controls.show_path = self.show_path;
+        controls.particle_color = color(self.particle_color);
+        controls.particle_stroke = color(self.particle_stroke);
+        controls.trail_length = self.trail_length;
+        controls.show_velocity = self.show_velocity;
+        controls.show_acceleration = self.show_acceleration;
+        controls.velocity_scale = self.velocity_scale;
+        controls.acceleration_scale = self.acceleration_scale;
+        controls.color_mode = color_mode;
+        controls.colormap = colormap;
+        return Ok(());
+    }
+}
+
+fn version_error(version: u32) -> anyhow::Error {
+    return anyhow!(
+        "unsupported snapshot version {}, expected {}",
+        version,
+        VERSION
+    );
+}
+
+impl ParticleState {
+    /// Captures a particle without a path
+    ///
+    /// ### Arguments
+    /// - `id` The particle's ID
+    /// - `p` The particle
+    pub fn new(id: u64, p: &Particle) -> Self {
+        return Self {
+            position: p.position(),
+            velocity: p.velocity(),
+            acceleration: p.acceleration(),
+            radius: p.radius(),
+            density: p.density(),
+            id,
+            path: Vec::new(),
+            path_capacity: crate::particle::path::DEFAULT_CAPACITY,
+            color: None,
+        };
+    }
+
+    /// Captures a particle and the path it has traced
+    pub fn with_path(id: u64, p: &Particle, path: &ParticlePath) -> Self {
+        return Self {
+            path: path.points.iter().copied().collect(),
+            path_capacity: path.capacity(),
+            ..Self::new(id, p)
+        };
+    }
+
+    /// Creates the saved particle
+    pub fn particle(&self) -> Result<Particle> {
+        let finite = self.position.is_finite()
+            && self.velocity.is_finite()
+            && self.acceleration.is_finite()
+            && self.radius.is_finite()
+            && self.density.is_finite();
+        if !finite {
+            return Err(anyhow!("all values must be finite"));
+        }
+        let mut p = Particle::default();
+        p.set_pos(self.position);
+        p.set_vel(self.velocity);
+        p.set_acc(self.acceleration);
+        p.set_radius(self.radius)?;
+        p.set_density(self.density)?;
+        return Ok(p);
+    }
+
+    /// Creates the saved path
+    pub fn path(&self) -> ParticlePath {
+        let mut path = ParticlePath::new(self.path_capacity);
+        for p in &self.path {
+            path.add_point(*p);
+        }
+        return path;
+    }
+}
@@ -0,0 +1,124 @@
+use crate::particle::Particle;
+use crate::physics;
+use crate::resources::camera::{CameraControls, CameraFollow, MAX_ZOOM, MIN_ZOOM};
+use crate::systems::input::{particle_at, window2world};
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::EguiContexts;
+
+/// How much one notch of the mouse wheel zooms by
+const ZOOM_STEP: f32 = 1.1;
+/// Pixels of touchpad scrolling treated as one notch of the mouse wheel
+const PIXELS_PER_LINE: f32 = 50.0;
+
+/// Zooms in and out with the mouse wheel, keeping the point under the cursor fixed
+pub fn zoom(
+    mut wheel: EventReader<MouseWheel>,
+    mut contexts: EguiContexts,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let lines: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    if lines == 0.0 || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+    let window = windows.single();
+    let (mut transform, mut projection) = camera.single_mut();
+
+    let scale = (projection.scale * ZOOM_STEP.powf(-lines)).clamp(MIN_ZOOM, MAX_ZOOM);
+    if let Some(cursor) = window.cursor_position() {
+        // Move the camera so the world position under the cursor is the same after zooming
+        let before = window2world(window, &transform, &projection, &cursor);
+        let offset = (before - transform.translation.truncate()) / projection.scale;
+        let after = before - offset * scale;
+        transform.translation = after.extend(transform.translation.z);
+    }
+    projection.scale = scale;
+}
+
+/// Pans the camera by dragging with the middle or right mouse button, which stops following
+pub fn pan(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut contexts: EguiContexts,
+    mut controls: ResMut<CameraControls>,
+    mut camera: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+) {
+    let delta: Vec2 = motion.read().map(|event| event.delta).sum();
+    let held = buttons.pressed(MouseButton::Middle) || buttons.pressed(MouseButton::Right);
+    if !held || delta == Vec2::ZERO || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+    let (mut transform, projection) = camera.single_mut();
+    // Window y points down, world y points up
+    transform.translation += Vec3::new(-delta.x, delta.y, 0.0) * projection.scale;
+    controls.follow = CameraFollow::Free;
+}
+
+/// Keyboard shortcuts for following
+///
+/// - `F` follows the particle under the cursor
+/// - `C` follows the centre of mass
+/// - `Escape` stops following
+pub fn follow_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
+    mut controls: ResMut<CameraControls>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    particles: Query<(Entity, &Particle)>,
+) {
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyC) {
+        controls.follow = CameraFollow::CenterOfMass;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        controls.follow = CameraFollow::Free;
+    }
+    if keys.just_pressed(KeyCode::KeyF) {
+        let window = windows.single();
+        let (transform, projection) = camera.single();
+        let Some(cursor) = window.cursor_position() else {
+            return;
+        };
+        let cursor = window2world(window, transform, projection, &cursor);
+        if let Some(entity) = particle_at(cursor, projection.scale, &particles) {
+            controls.follow = CameraFollow::Particle(entity);
+        }
+    }
+}
+
+/// Centres the camera on whatever it is following
+pub fn follow(
+    mut controls: ResMut<CameraControls>,
+    particles: Query<&Particle>,
+    mut camera: Query<&mut Transform, With<Camera2d>>,
+) {
+    let target = match controls.follow {
+        CameraFollow::Free => return,
+        CameraFollow::CenterOfMass => {
+            let particles: Vec<Particle> = particles.iter().cloned().collect();
+            physics::center_of_mass(&particles)
+        }
+        CameraFollow::Particle(entity) => match particles.get(entity) {
+            Ok(p) => Some(p.position()),
+            Err(_) => {
+                controls.follow = CameraFollow::Free;
+                None
+            }
+        },
+    };
+    if let Some(target) = target {
+        let mut transform = camera.single_mut();
+        transform.translation = target.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::resources::clock::SimulationClock;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy::utils::Instant;
+
+/// Schedule containing the systems that advance the physics by one fixed timestep
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsSchedule;
+
+/// Runs the physics schedule as many times as the accumulated frame time allows, and measures how
+/// long each step takes
+pub fn run_physics(world: &mut World) {
+    let frame_dt = world.resource::<Time>().delta_seconds();
+    let steps = world.resource_mut::<SimulationClock>().advance(frame_dt);
+    if steps == 0 {
+        return;
+    }
+    let start = Instant::now();
+    let _ = world.try_schedule_scope(PhysicsSchedule, |world, schedule| {
+        for _ in 0..steps {
+            world.resource_mut::<SimulationClock>().start_step();
+            schedule.run(world);
+        }
+    });
+    world.resource_mut::<SimulationClock>().step_time =
+        start.elapsed().as_secs_f32() / steps as f32;
+}
@@ -0,0 +1,106 @@
+use crate::particle::Particle;
+use crate::physics::diagnostics::ConservedQuantities;
+use crate::resources;
+use crate::resources::clock::SimulationClock;
+use crate::systems;
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+
+/// Adds energy, momentum, angular momentum and centre of mass diagnostics, measured once per frame
+/// after the physics has run
+///
+/// Measuring is O(n²), so it is kept out of the physics schedule where it would run every step
+pub struct ConservationDiagnosticsPlugin;
+
+impl Plugin for ConservationDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        for path in Self::ALL {
+            app.register_diagnostic(Diagnostic::new(path).with_smoothing_factor(0.0));
+        }
+        app.insert_resource(ConservationBaseline::default())
+            .add_systems(
+                Update,
+                Self::diagnostic_system.after(systems::clock::run_physics),
+            );
+    }
+}
+
+/// The measurement that drifts are reported relative to
+///
+/// Reset whenever the number of particles changes (spawning or merging changes the totals), or G
+/// or the softening length is changed (which changes the potential energy)
+#[derive(Resource, Default)]
+pub struct ConservationBaseline {
+    pub initial: Option<ConservedQuantities>,
+    count: usize,
+    g: f32,
+    softening: f32,
+}
+
+impl ConservationDiagnosticsPlugin {
+    pub const KINETIC_ENERGY: DiagnosticPath = DiagnosticPath::const_new("kinetic_energy");
+    pub const POTENTIAL_ENERGY: DiagnosticPath = DiagnosticPath::const_new("potential_energy");
+    pub const TOTAL_ENERGY: DiagnosticPath = DiagnosticPath::const_new("total_energy");
+    pub const MOMENTUM: DiagnosticPath = DiagnosticPath::const_new("momentum");
+    pub const ANGULAR_MOMENTUM: DiagnosticPath = DiagnosticPath::const_new("angular_momentum");
+    pub const CENTER_OF_MASS_X: DiagnosticPath = DiagnosticPath::const_new("center_of_mass/x");
+    pub const CENTER_OF_MASS_Y: DiagnosticPath = DiagnosticPath::const_new("center_of_mass/y");
+    pub const ENERGY_DRIFT: DiagnosticPath = DiagnosticPath::const_new("drift/energy");
+    pub const MOMENTUM_DRIFT: DiagnosticPath = DiagnosticPath::const_new("drift/momentum");
+    pub const ANGULAR_MOMENTUM_DRIFT: DiagnosticPath =
+        DiagnosticPath::const_new("drift/angular_momentum");
+
+    const ALL: [DiagnosticPath; 10] = [
+        Self::KINETIC_ENERGY,
+        Self::POTENTIAL_ENERGY,
+        Self::TOTAL_ENERGY,
+        Self::MOMENTUM,
+        Self::ANGULAR_MOMENTUM,
+        Self::CENTER_OF_MASS_X,
+        Self::CENTER_OF_MASS_Y,
+        Self::ENERGY_DRIFT,
+        Self::MOMENTUM_DRIFT,
+        Self::ANGULAR_MOMENTUM_DRIFT,
+    ];
+
+    pub fn diagnostic_system(
+        mut diagnostics: Diagnostics,
+        mut baseline: ResMut<ConservationBaseline>,
+        query: Query<&Particle>,
+        state: Res<resources::SimulationState>,
+        clock: Res<SimulationClock>,
+    ) {
+        // Nothing has moved while paused
+        if clock.last_advance() == 0 && baseline.initial.is_some() {
+            return;
+        }
+        let particles: Vec<Particle> = query.iter().cloned().collect();
+        let g = state.settings.constants.g.value;
+        let softening = state.settings.constants.softening.value;
+        let q = ConservedQuantities::measure(&particles, g, softening);
+        if baseline.initial.is_none()
+            || baseline.count != particles.len()
+            || baseline.g != g
+            || baseline.softening != softening
+        {
+            baseline.initial = Some(q);
+            baseline.count = particles.len();
+            baseline.g = g;
+            baseline.softening = softening;
+        }
+        let initial = baseline.initial.unwrap_or(q);
+
+        diagnostics.add_measurement(&Self::KINETIC_ENERGY, || q.kinetic_energy);
+        diagnostics.add_measurement(&Self::POTENTIAL_ENERGY, || q.potential_energy);
+        diagnostics.add_measurement(&Self::TOTAL_ENERGY, || q.total_energy());
+        diagnostics.add_measurement(&Self::MOMENTUM, || q.momentum.length());
+        diagnostics.add_measurement(&Self::ANGULAR_MOMENTUM, || q.angular_momentum);
+        diagnostics.add_measurement(&Self::CENTER_OF_MASS_X, || q.center_of_mass.x);
+        diagnostics.add_measurement(&Self::CENTER_OF_MASS_Y, || q.center_of_mass.y);
+        diagnostics.add_measurement(&Self::ENERGY_DRIFT, || q.energy_drift(&initial));
+        diagnostics.add_measurement(&Self::MOMENTUM_DRIFT, || q.momentum_drift(&initial));
+        diagnostics.add_measurement(&Self::ANGULAR_MOMENTUM_DRIFT, || {
+            q.angular_momentum_drift(&initial)
+        });
+    }
+}
@@ -0,0 +1,98 @@
+use crate::export::TrajectoryWriter;
+use crate::particle::{Particle, ParticleId};
+use crate::resources::clock::SimulationClock;
+use crate::resources::export::{ExportRequest, NextParticleId, TrajectoryExport};
+use anyhow::Result;
+use bevy::prelude::*;
+
+/// Gives every new particle the next unused ID
+pub fn assign_ids(
+    mut commands: Commands,
+    mut next_id: ResMut<NextParticleId>,
+    new: Query<Entity, (With<Particle>, Without<ParticleId>)>,
+) {
+    for entity in new.iter() {
+        commands.entity(entity).insert(ParticleId(next_id.0));
+        next_id.0 += 1;
+    }
+}
+
+/// Starts and stops the recording when requested, a recording starts with a record of every
+/// particle
+///
+/// A failure ends the recording and reports the error in the GUI
+pub fn control(
+    mut export: ResMut<TrajectoryExport>,
+    clock: Res<SimulationClock>,
+    particles: Query<(&ParticleId, &Particle)>,
+) {
+    let result = match export.request.take() {
+        Some(ExportRequest::Start) => start(&mut export, &clock, &particles),
+        Some(ExportRequest::Stop) => stop(&mut export),
+        None => Ok(()),
+    };
+    report(&mut export, result);
+}
+
+/// Records every particle after each physics step whose number is a multiple of `every`, the same
+/// cadence as the batch runner
+pub fn record(
+    mut export: ResMut<TrajectoryExport>,
+    clock: Res<SimulationClock>,
+    particles: Query<(&ParticleId, &Particle)>,
+) {
+    let (step, _) = clock.current_step();
+    if export.writer.is_none() || step % export.every.max(1) != 0 {
+        return;
+    }
+    let result = write(&mut export, &clock, &particles);
+    report(&mut export, result);
+}
+
+/// Ends the recording if it failed
+fn report(export: &mut TrajectoryExport, result: Result<()>) {
+    if let Err(e) = result {
+        warn!("{:#}", e);
+        export.error = Some(format!("{:#}", e));
+        export.writer = None;
+    }
+}
+
+fn start(
+    export: &mut TrajectoryExport,
+    clock: &SimulationClock,
+    particles: &Query<(&ParticleId, &Particle)>,
+) -> Result<()> {
+    // Finish any recording already running first
+    stop(export)?;
+    let path = std::path::Path::new(export.path.trim()).to_path_buf();
+    export.writer = Some(TrajectoryWriter::create(&path)?);
+    export.error = None;
+    info!("Recording trajectories to '{}'", path.display());
+    return write(export, clock, particles);
+}
+
+fn stop(export: &mut TrajectoryExport) -> Result<()> {
+    if let Some(mut writer) = export.writer.take() {
+        writer.finish()?;
+        info!("Recorded {} trajectory rows", writer.rows());
+    }
+    return Ok(());
+}
+
+/// Writes a row for every particle, in ID order, at the time of the current step
+fn write(
+    export: &mut TrajectoryExport,
+    clock: &SimulationClock,
+    particles: &Query<(&ParticleId, &Particle)>,
+) -> Result<()> {
+    let Some(writer) = &mut export.writer else {
+        return Ok(());
+    };
+    let mut rows: Vec<(u64, Particle)> =
+        particles.iter().map(|(id, p)| (id.0, p.clone())).collect();
+    rows.sort_by_key(|(id, _)| *id);
+    let (ids, particles): (Vec<u64>, Vec<Particle>) = rows.into_iter().unzip();
+    let (_, time) = clock.current_step();
+    return writer.write(time, &ids, &particles);
+}
@@ -0,0 +1,229 @@
+use crate::particle::Particle;
+use crate::physics::field::FieldGrid;
+use crate::resources;
+use crate::resources::field::FieldOverlay;
+use crate::utils;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_prototype_lyon::prelude::*;
+
+const CONTOUR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+const ARROW_COLOR: Color = Color::rgba(0.6, 0.8, 1.0, 0.5);
+/// Roughly how many field arrows are drawn across the longer side of the window
+const ARROWS_ACROSS: usize = 24;
+
+/// The heatmap sprite
+#[derive(Component)]
+pub struct FieldImage;
+
+/// The contour lines
+#[derive(Component)]
+pub struct FieldContours;
+
+/// The field direction arrows
+#[derive(Component)]
+pub struct FieldArrows;
+
+type ImageLayer = (
+    &'static Handle<Image>,
+    &'static mut Sprite,
+    &'static mut Transform,
+    &'static mut Visibility,
+);
+type LineLayer = (
+    &'static mut Path,
+    &'static mut Stroke,
+    &'static mut Visibility,
+);
+
+/// Keeps the arrows' query disjoint from the other layers
+type OnlyArrows = (
+    With<FieldArrows>,
+    Without<FieldImage>,
+    Without<FieldContours>,
+);
+
+/// The persistent entities the overlay is drawn with
+#[derive(SystemParam)]
+pub struct FieldLayers<'w, 's> {
+    images: ResMut<'w, Assets<Image>>,
+    image: Query<'w, 's, ImageLayer, (With<FieldImage>, Without<Camera2d>)>,
+    contours: Query<'w, 's, LineLayer, (With<FieldContours>, Without<FieldImage>)>,
+    arrows: Query<'w, 's, LineLayer, OnlyArrows>,
+}
+
+impl FieldLayers<'_, '_> {
+    fn hide(&mut self) {
+        for (_, _, _, mut visibility) in self.image.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        for (_, _, mut visibility) in self.contours.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        for (_, _, mut visibility) in self.arrows.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Creates the overlay's entities, hidden and behind everything else
+pub fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    commands.spawn((
+        SpriteBundle {
+            texture: images.add(image),
+            transform: Transform::from_xyz(0.0, 0.0, -3.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        FieldImage,
+    ));
+    commands.spawn((line_layer(CONTOUR_COLOR, -2.5), FieldContours));
+    commands.spawn((line_layer(ARROW_COLOR, -2.0), FieldArrows));
+}
+
+/// A hidden, empty shape for lines to be drawn into
+fn line_layer(color: Color, z: f32) -> (ShapeBundle, Stroke) {
+    let shape = ShapeBundle {
+        spatial: SpatialBundle {
+            transform: Transform::from_xyz(0.0, 0.0, z),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ..default()
+    };
+    return (shape, Stroke::new(color, 1.0));
+}
+
+/// Samples the field over the visible area and redraws the overlay, at the overlay's rate
+///
+/// Changing the overlay's settings redraws it straight away
+pub fn update(
+    time: Res<Time>,
+    mut since_update: Local<f32>,
+    overlay: Res<FieldOverlay>,
+    state: Res<resources::SimulationState>,
+    particles: Query<&Particle>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut layers: FieldLayers,
+) {
+    if !overlay.enabled {
+        if overlay.is_changed() {
+            layers.hide();
+        }
+        return;
+    }
+    *since_update += time.delta_seconds();
+    if *since_update < 1.0 / overlay.rate.max(0.1) && !overlay.is_changed() {
+        return;
+    }
+    *since_update = 0.0;
+
+    let (transform, projection) = camera.single();
+    let center = transform.translation.truncate();
+    let (min, max) = (center + projection.area.min, center + projection.area.max);
+    let size = max - min;
+    let resolution = overlay.resolution.max(2) as f32;
+    let cell = size.max_element() / resolution;
+    let (width, height) = (
+        (size.x / cell).ceil() as usize,
+        (size.y / cell).ceil() as usize,
+    );
+
+    let constants = &state.settings.constants;
+    let particles: Vec<Particle> = particles.iter().cloned().collect();
+    let grid = FieldGrid::sample(
+        &particles,
+        min,
+        max,
+        width,
+        height,
+        constants.g.value,
+        constants.softening.value,
+    );
+    let values = grid.magnitudes(overlay.quantity);
+    let (low, high) = values.iter().fold((f32::MAX, f32::MIN), |(low, high), v| {
+        (low.min(*v), high.max(*v))
+    });
+
+    // Texture rows run from the top down, grid rows from the bottom up
+    let alpha = (overlay.opacity.clamp(0.0, 1.0) * 255.0) as u8;
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let value = values[y * width + x];
+            let t = if high > low {
+                (value - low) / (high - low)
+            } else {
+                0.0
+            };
+            let [r, g, b] = overlay.colormap.sample(t);
+            data.extend([r, g, b].map(|c| (c * 255.0) as u8));
+            data.push(alpha);
+        }
+    }
+    for (handle, mut sprite, mut sprite_transform, mut visibility) in layers.image.iter_mut() {
+        if let Some(image) = layers.images.get_mut(handle) {
+            *image = Image::new(
+                Extent3d {
+                    width: width as u32,
+                    height: height as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                data.clone(),
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+        }
+        sprite.custom_size = Some(size);
+        sprite_transform.translation = ((min + max) / 2.0).extend(sprite_transform.translation.z);
+        *visibility = Visibility::Visible;
+    }
+
+    // Lines stay one pixel wide at any zoom
+    let line_width = projection.scale;
+    for (mut path, mut stroke, mut visibility) in layers.contours.iter_mut() {
+        if overlay.contours {
+            let mut builder = PathBuilder::new();
+            for (start, end) in grid.contours(&values, overlay.contour_levels) {
+                builder.move_to(start);
+                builder.line_to(end);
+            }
+            *path = builder.build();
+            stroke.options.line_width = line_width;
+            *visibility = Visibility::Visible;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+    for (mut path, mut stroke, mut visibility) in layers.arrows.iter_mut() {
+        if overlay.arrows {
+            let stride = (width.max(height) / ARROWS_ACROSS).max(1);
+            let length = cell * stride as f32 * 0.7;
+            let mut arrows = Vec::new();
+            for y in (stride / 2..height).step_by(stride) {
+                for x in (stride / 2..width).step_by(stride) {
+                    let direction = grid.field[y * width + x].normalize_or_zero();
+                    if direction != Vec2::ZERO {
+                        let start = grid.position(x, y) - direction * length / 2.0;
+                        arrows.push((start, start + direction * length));
+                    }
+                }
+            }
+            *path = utils::arrows_path(&arrows);
+            stroke.options.line_width = line_width;
+            *visibility = Visibility::Visible;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
@@ -1,7 +1,27 @@
+use crate::colormap::{ColorMode, ColorScale, Colormap};
+use crate::generators::GeneratorKind;
 use crate::particle::Particle;
+use crate::physics::field::FieldQuantity;
+use crate::physics::integrator::IntegratorKind;
+use crate::physics::orbit::OrbitTarget;
+use crate::physics::{CollisionMode, ForceSolver};
+use crate::presets::Preset;
 use crate::resources;
+use crate::resources::camera::{CameraControls, CameraFollow};
+use crate::resources::clock::{SimulationClock, MAX_TIME_SCALE, MIN_TIME_SCALE};
 use crate::resources::constants;
-use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use crate::resources::export::{ExportRequest, TrajectoryExport};
+use crate::resources::field::FieldOverlay;
+use crate::resources::frame::{ReferenceFrame, TrailFrame};
+use crate::resources::history::HistoryPlot;
+use crate::resources::scenario::{ScenarioFile, ScenarioRequest};
+use crate::resources::snapshot::{SnapshotFile, SnapshotRequest};
+use crate::resources::spawn::{SpawnSettings, SpawnTool};
+use crate::simulation;
+use crate::systems::diagnostics::ConservationDiagnosticsPlugin as Conservation;
+use crate::systems::particles::PREDICTION_BARNES_HUT_ABOVE;
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
@@ -15,6 +35,10 @@ fn stats_section(
         Some(fps) => fps.average().unwrap_or(0.0),
         None => 0.0,
     };
+    let value = |path: &DiagnosticPath| match diagnostics.get(path) {
+        Some(diagnostic) => diagnostic.value().unwrap_or(0.0),
+        None => 0.0,
+    };
     egui::Grid::new("sim_grid")
         .num_columns(2)
         .spacing([40.0, 4.0])
@@ -23,6 +47,38 @@ fn stats_section(
             let labels = vec![
                 ("# of Particles:", format!("{}", particles)),
                 ("FPS:", format!("{:.1}", fps)),
+                (
+                    "Total Energy:",
+                    format!("{:.4e}", value(&Conservation::TOTAL_ENERGY)),
+                ),
+                (
+                    "Kinetic Energy:",
+                    format!("{:.4e}", value(&Conservation::KINETIC_ENERGY)),
+                ),
+                (
+                    "Potential Energy:",
+                    format!("{:.4e}", value(&Conservation::POTENTIAL_ENERGY)),
+                ),
+                (
+                    "Energy Drift:",
+                    format!("{:.2e}", value(&Conservation::ENERGY_DRIFT)),
+                ),
+                (
+                    "Momentum Drift:",
+                    format!("{:.2e}", value(&Conservation::MOMENTUM_DRIFT)),
+                ),
+                (
+                    "Angular Momentum Drift:",
+                    format!("{:.2e}", value(&Conservation::ANGULAR_MOMENTUM_DRIFT)),
+                ),
+                (
+                    "Centre of Mass:",
+                    format!(
+                        "({:.1}, {:.1})",
+                        value(&Conservation::CENTER_OF_MASS_X),
+                        value(&Conservation::CENTER_OF_MASS_Y)
+                    ),
+                ),
             ];
             for (label, value) in labels {
                 ui.label(label);
@@ -32,7 +88,11 @@ fn stats_section(
         });
 }
 
-fn params_section(ui: &mut egui::Ui, numeric_constants: &mut constants::NumericConstants) {
+fn params_section(
+    ui: &mut egui::Ui,
+    numeric_constants: &mut constants::NumericConstants,
+    collision_mode: &mut CollisionMode,
+) {
     egui::Grid::new("params_grid")
         .num_columns(2)
         .spacing([40.0, 4.0])
@@ -47,38 +107,601 @@ fn params_section(ui: &mut egui::Ui, numeric_constants: &mut constants::NumericC
                 );
                 ui.end_row();
             }
+
+            ui.label("Collisions");
+            egui::ComboBox::from_id_source("collision_mode")
+                .selected_text(collision_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in CollisionMode::ALL {
+                        ui.selectable_value(collision_mode, mode, mode.label());
+                    }
+                });
+            ui.end_row();
+        });
+}
+
+fn clock_section(ui: &mut egui::Ui, clock: &mut SimulationClock) {
+    ui.horizontal(|ui| {
+        let label = if clock.paused { "Resume" } else { "Pause" };
+        if ui.button(label).on_hover_text("Space").clicked() {
+            clock.toggle_pause();
+        }
+        if ui.button("Step").on_hover_text(".").clicked() {
+            clock.step_once();
+        }
+    });
+    egui::Grid::new("clock_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Time Scale");
+            ui.add(
+                egui::Slider::new(&mut clock.time_scale, MIN_TIME_SCALE..=MAX_TIME_SCALE)
+                    .logarithmic(true),
+            )
+            .on_hover_text("[ and ]");
+            ui.end_row();
+
+            ui.label("Timestep (s)");
+            ui.add(
+                egui::DragValue::new(&mut clock.dt)
+                    .speed(0.0001)
+                    .clamp_range(0.0001..=0.1),
+            );
+            ui.end_row();
+
+            ui.label("Substeps");
+            ui.add(egui::DragValue::new(&mut clock.substeps).clamp_range(1..=64));
+            ui.end_row();
+
+            ui.label("Max Steps per Frame");
+            ui.add(egui::DragValue::new(&mut clock.max_steps_per_frame).clamp_range(1..=128))
+                .on_hover_text("Raise this if high time scales run slower than they should");
+            ui.end_row();
+
+            ui.label("Simulated Time:");
+            ui.label(format!("{:.2} s", clock.elapsed()));
+            ui.end_row();
         });
+    let dropped = clock.dropped_fraction();
+    if dropped > 0.0 {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            format!(
+                "Running at {:.2}x: {:.0}% of the time is dropped by the step limit",
+                clock.time_scale * (1.0 - dropped),
+                dropped * 100.0
+            ),
+        );
+    }
 }
 
-fn controls_section(ui: &mut egui::Ui, controls: &mut resources::controls::Controls) {
+fn controls_section(
+    ui: &mut egui::Ui,
+    controls: &mut resources::controls::Controls,
+    settings: &mut simulation::Settings,
+    tools: &mut GuiTools,
+) {
     egui::Grid::new("control_grid")
         .num_columns(2)
         .spacing([40.0, 4.0])
         .striped(true)
         .show(ui, |ui| {
             ui.label("Trace Path");
-            ui.checkbox(&mut controls.show_path, "");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut controls.show_path, "");
+                ui.add(
+                    egui::DragValue::new(&mut controls.trail_length)
+                        .clamp_range(2..=5000)
+                        .suffix(" points"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Velocity Arrows");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut controls.show_velocity, "");
+                ui.add(
+                    egui::Slider::new(&mut controls.velocity_scale, 1e-3..=10.0)
+                        .logarithmic(true)
+                        .text("scale"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Acceleration Arrows");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut controls.show_acceleration, "");
+                ui.add(
+                    egui::Slider::new(&mut controls.acceleration_scale, 1e-3..=10.0)
+                        .logarithmic(true)
+                        .text("scale"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Force Solver");
+            egui::ComboBox::from_id_source("force_solver")
+                .selected_text(settings.force_solver.label())
+                .show_ui(ui, |ui| {
+                    for solver in ForceSolver::ALL {
+                        ui.selectable_value(&mut settings.force_solver, solver, solver.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Integrator");
+            egui::ComboBox::from_id_source("integrator")
+                .selected_text(settings.integrator.label())
+                .show_ui(ui, |ui| {
+                    for integrator in IntegratorKind::ALL {
+                        ui.selectable_value(
+                            &mut settings.integrator,
+                            integrator,
+                            integrator.label(),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Trail Frame");
+            let trail_frame = &mut tools.trail_frame.frame;
+            egui::ComboBox::from_id_source("trail_frame")
+                .selected_text(trail_frame.label())
+                .show_ui(ui, |ui| {
+                    for frame in [ReferenceFrame::Inertial, ReferenceFrame::CenterOfMass] {
+                        ui.selectable_value(trail_frame, frame, frame.label());
+                    }
+                })
+                .response
+                .on_hover_text("Frames that follow particles are chosen in the inspector");
+            ui.end_row();
+
+            ui.label("Camera");
+            let camera = &mut tools.camera;
+            egui::ComboBox::from_id_source("camera_follow")
+                .selected_text(camera.follow.label())
+                .show_ui(ui, |ui| {
+                    for follow in [CameraFollow::Free, CameraFollow::CenterOfMass] {
+                        ui.selectable_value(&mut camera.follow, follow, follow.label());
+                    }
+                })
+                .response
+                .on_hover_text("F follows the particle under the cursor, C the centre of mass");
             ui.end_row();
         });
 }
 
+fn scenario_section(ui: &mut egui::Ui, file: &mut ScenarioFile) {
+    egui::Grid::new("scenario_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("File");
+            ui.add(egui::TextEdit::singleline(&mut file.path).hint_text("built-in"));
+            ui.end_row();
+        });
+    if ui.button("Reload").clicked() {
+        file.request = Some(ScenarioRequest::File);
+    }
+
+    let generator = &mut file.generator;
+    egui::Grid::new("generator_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Generator");
+            egui::ComboBox::from_id_source("generator")
+                .selected_text(generator.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in GeneratorKind::ALL {
+                        ui.selectable_value(&mut generator.kind, kind, kind.label());
+                    }
+                });
+            ui.end_row();
+
+            if generator.kind.has_count() {
+                ui.label("Particles");
+                ui.add(egui::DragValue::new(&mut generator.count).clamp_range(1..=5000));
+                ui.end_row();
+            }
+
+            ui.label("Size");
+            ui.add(
+                egui::DragValue::new(&mut generator.size)
+                    .speed(1.0)
+                    .clamp_range(1.0..=2000.0),
+            );
+            ui.end_row();
+
+            ui.label("Seed");
+            ui.add(egui::DragValue::new(&mut generator.seed));
+            ui.end_row();
+        });
+    if ui.button("Generate").clicked() {
+        file.request = Some(ScenarioRequest::Generator);
+    }
+
+    let preset = &mut file.preset;
+    egui::Grid::new("preset_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Preset");
+            egui::ComboBox::from_id_source("preset")
+                .selected_text(preset.preset.label())
+                .show_ui(ui, |ui| {
+                    for kind in Preset::ALL {
+                        ui.selectable_value(&mut preset.preset, kind, kind.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Size");
+            ui.add(
+                egui::DragValue::new(&mut preset.size)
+                    .speed(1.0)
+                    .clamp_range(1.0..=2000.0),
+            );
+            ui.end_row();
+
+            ui.label("Body Mass");
+            let speed = preset.mass * 0.01;
+            ui.add(
+                egui::DragValue::new(&mut preset.mass)
+                    .speed(speed)
+                    .clamp_range(1.0..=1e9),
+            );
+            ui.end_row();
+        });
+    if ui.button("Load Preset").clicked() {
+        file.request = Some(ScenarioRequest::Preset);
+    }
+    if let Some(error) = &file.error {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+}
+
+fn file_section(ui: &mut egui::Ui, file: &mut SnapshotFile) {
+    egui::Grid::new("file_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Snapshot");
+            ui.text_edit_singleline(&mut file.path);
+            ui.end_row();
+        });
+    ui.horizontal(|ui| {
+        if ui.button("Save").clicked() {
+            file.request = Some(SnapshotRequest::Save);
+        }
+        if ui.button("Load").clicked() {
+            file.request = Some(SnapshotRequest::Load);
+        }
+    });
+    if let Some(error) = &file.error {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+}
+
+fn trajectory_section(ui: &mut egui::Ui, export: &mut TrajectoryExport) {
+    egui::Grid::new("trajectory_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Trajectory");
+            ui.text_edit_singleline(&mut export.path)
+                .on_hover_text("A .csv, .npy or .npz file");
+            ui.end_row();
+
+            ui.label("Record Every");
+            ui.add(
+                egui::DragValue::new(&mut export.every)
+                    .clamp_range(1..=10000)
+                    .suffix(" steps"),
+            );
+            ui.end_row();
+        });
+    ui.horizontal(|ui| match &export.writer {
+        Some(writer) => {
+            if ui.button("Stop Recording").clicked() {
+                export.request = Some(ExportRequest::Stop);
+            }
+            ui.label(format!("{} rows", writer.rows()));
+        }
+        None => {
+            if ui.button("Record").clicked() {
+                export.request = Some(ExportRequest::Start);
+            }
+        }
+    });
+    if let Some(error) = &export.error {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+}
+
+fn spawn_section(ui: &mut egui::Ui, spawn: &mut SpawnSettings) {
+    egui::Grid::new("spawn_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Tool");
+            egui::ComboBox::from_id_source("spawn_tool")
+                .selected_text(spawn.tool.label())
+                .show_ui(ui, |ui| {
+                    for tool in SpawnTool::ALL {
+                        ui.selectable_value(&mut spawn.tool, tool, tool.label());
+                    }
+                })
+                .response
+                .on_hover_text(format!(
+                    "Trajectory previews use Barnes-Hut above {} particles",
+                    PREDICTION_BARNES_HUT_ABOVE
+                ));
+            ui.end_row();
+
+            ui.label("Radius");
+            ui.add(
+                egui::DragValue::new(&mut spawn.radius)
+                    .speed(0.1)
+                    .clamp_range(0.5..=100.0),
+            );
+            ui.end_row();
+
+            ui.label("Density");
+            ui.add(
+                egui::DragValue::new(&mut spawn.density)
+                    .speed(0.01)
+                    .clamp_range(0.001..=1e5),
+            );
+            ui.end_row();
+
+            if spawn.tool == SpawnTool::Orbit {
+                ui.label("Orbit");
+                egui::ComboBox::from_id_source("orbit_target")
+                    .selected_text(spawn.target.label())
+                    .show_ui(ui, |ui| {
+                        for target in OrbitTarget::ALL {
+                            ui.selectable_value(&mut spawn.target, target, target.label());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Eccentricity");
+                ui.add(egui::Slider::new(&mut spawn.eccentricity, 0.0..=0.95));
+                ui.end_row();
+
+                ui.label("Anti-clockwise");
+                ui.checkbox(&mut spawn.prograde, "")
+                    .on_hover_text("Hold shift while clicking to reverse");
+                ui.end_row();
+            }
+        });
+}
+
+fn color_picker(ui: &mut egui::Ui, label: &str, color: &mut Color) {
+    ui.label(label);
+    let mut rgba = color.as_rgba_f32();
+    if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+        let [r, g, b, a] = rgba;
+        *color = Color::rgba(r, g, b, a);
+    }
+    ui.end_row();
+}
+
+/// Draws the colormap as a bar labelled with the range it covers
+fn color_legend(ui: &mut egui::Ui, colormap: Colormap, scale: &ColorScale) {
+    const SEGMENTS: usize = 64;
+    let width = ui.available_width().min(240.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 12.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let segment = rect.width() / SEGMENTS as f32;
+    for i in 0..SEGMENTS {
+        let [r, g, b] = colormap.sample((i as f32 + 0.5) / SEGMENTS as f32);
+        let left = rect.left() + i as f32 * segment;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left, rect.top()),
+            // Overlap slightly so no gaps show between segments
+            egui::pos2(left + segment + 0.5, rect.bottom()),
+        );
+        let color =
+            egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
+        painter.rect_filled(bar, 0.0, color);
+    }
+    ui.horizontal(|ui| {
+        ui.label(format!("{:.3e}", scale.min));
+        if scale.mode.logarithmic() {
+            ui.label("(log)");
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(format!("{:.3e}", scale.max));
+        });
+    });
+}
+
+fn color_section(
+    ui: &mut egui::Ui,
+    controls: &mut resources::controls::Controls,
+    particles_query: &Query<&Particle>,
+) {
+    egui::Grid::new("color_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Colour By");
+            egui::ComboBox::from_id_source("color_mode")
+                .selected_text(controls.color_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in ColorMode::ALL {
+                        ui.selectable_value(&mut controls.color_mode, mode, mode.label());
+                    }
+                });
+            ui.end_row();
+
+            if controls.color_mode.is_continuous() {
+                ui.label("Colormap");
+                egui::ComboBox::from_id_source("colormap")
+                    .selected_text(controls.colormap.label())
+                    .show_ui(ui, |ui| {
+                        for colormap in Colormap::ALL {
+                            ui.selectable_value(&mut controls.colormap, colormap, colormap.label());
+                        }
+                    });
+                ui.end_row();
+            }
+
+            color_picker(ui, "New Particles", &mut controls.particle_color);
+            color_picker(ui, "Outline", &mut controls.particle_stroke);
+        });
+
+    match controls.color_mode {
+        ColorMode::Assigned => {
+            ui.label("Particles keep the colour they were created with");
+        }
+        ColorMode::Id => {
+            ui.label("Each particle has its own hue");
+        }
+        mode => match ColorScale::new(mode, particles_query.iter()) {
+            Some(scale) => color_legend(ui, controls.colormap, &scale),
+            None => {
+                ui.label("No particles");
+            }
+        },
+    }
+}
+
+fn field_section(ui: &mut egui::Ui, overlay: &mut FieldOverlay) {
+    egui::Grid::new("field_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Show");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut overlay.enabled, "");
+                egui::ComboBox::from_id_source("field_quantity")
+                    .selected_text(overlay.quantity.label())
+                    .show_ui(ui, |ui| {
+                        for quantity in FieldQuantity::ALL {
+                            ui.selectable_value(&mut overlay.quantity, quantity, quantity.label());
+                        }
+                    });
+            });
+            ui.end_row();
+
+            if !overlay.enabled {
+                return;
+            }
+            ui.label("Colormap");
+            egui::ComboBox::from_id_source("field_colormap")
+                .selected_text(overlay.colormap.label())
+                .show_ui(ui, |ui| {
+                    for colormap in Colormap::ALL {
+                        ui.selectable_value(&mut overlay.colormap, colormap, colormap.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Opacity");
+            ui.add(egui::Slider::new(&mut overlay.opacity, 0.0..=1.0));
+            ui.end_row();
+
+            ui.label("Contours");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut overlay.contours, "");
+                ui.add(egui::DragValue::new(&mut overlay.contour_levels).clamp_range(1..=64));
+            });
+            ui.end_row();
+
+            ui.label("Field Arrows");
+            ui.checkbox(&mut overlay.arrows, "");
+            ui.end_row();
+
+            ui.label("Resolution");
+            ui.add(egui::DragValue::new(&mut overlay.resolution).clamp_range(16..=512));
+            ui.end_row();
+
+            ui.label("Updates per Second");
+            ui.add(
+                egui::DragValue::new(&mut overlay.rate)
+                    .speed(0.1)
+                    .clamp_range(0.1..=60.0),
+            );
+            ui.end_row();
+        });
+}
+
+/// The files the GUI can load from and save to
+#[derive(SystemParam)]
+pub struct GuiFiles<'w> {
+    scenario: ResMut<'w, ScenarioFile>,
+    snapshot: ResMut<'w, SnapshotFile>,
+    history: ResMut<'w, HistoryPlot>,
+    trajectory: ResMut<'w, TrajectoryExport>,
+}
+
+/// The camera and spawning tools the GUI controls
+#[derive(SystemParam)]
+pub struct GuiTools<'w> {
+    camera: ResMut<'w, CameraControls>,
+    spawn: ResMut<'w, SpawnSettings>,
+    trail_frame: ResMut<'w, TrailFrame>,
+    field: ResMut<'w, FieldOverlay>,
+}
+
 pub fn gui(
     mut contexts: EguiContexts,
-    mut state: ResMut<resources::SimulationState>,
+    state: ResMut<resources::SimulationState>,
+    mut clock: ResMut<SimulationClock>,
+    mut files: GuiFiles,
+    mut tools: GuiTools,
     particles_query: Query<&Particle>,
     diagnostics: Res<DiagnosticsStore>,
 ) {
+    let state = state.into_inner();
     egui::Window::new("n-body")
         .resizable([true, false])
         .default_width(280.0)
         .show(contexts.ctx_mut(), |ui| {
+            ui.heading("File");
+            file_section(ui, &mut files.snapshot);
+            trajectory_section(ui, &mut files.trajectory);
+            ui.separator();
+            ui.heading("Scenarios");
+            scenario_section(ui, &mut files.scenario);
+            ui.separator();
             ui.heading("Simulation");
             stats_section(ui, &particles_query, &diagnostics);
+            ui.toggle_value(&mut files.history.open, "History Plots");
             ui.separator();
             ui.heading("Parameters");
-            params_section(ui, &mut state.numeric_constants);
+            params_section(
+                ui,
+                &mut state.settings.constants,
+                &mut state.settings.collision_mode,
+            );
+            ui.separator();
+            ui.heading("Time");
+            clock_section(ui, &mut clock);
             ui.separator();
             ui.heading("Controls");
-            controls_section(ui, &mut state.controls);
+            controls_section(ui, &mut state.controls, &mut state.settings, &mut tools);
+            ui.separator();
+            ui.heading("Colour");
+            color_section(ui, &mut state.controls, &particles_query);
+            ui.separator();
+            ui.heading("Field");
+            field_section(ui, &mut tools.field);
+            ui.separator();
+            ui.heading("Spawn");
+            spawn_section(ui, &mut tools.spawn);
         });
 }
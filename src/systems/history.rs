@@ -0,0 +1,137 @@
+use crate::history::{History, Sample, Series};
+use crate::particle::Particle;
+use crate::resources::clock::SimulationClock;
+use crate::resources::history::HistoryPlot;
+use crate::systems::diagnostics::ConservationDiagnosticsPlugin as Conservation;
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+/// Height of each plot in the history window
+const PLOT_HEIGHT: f32 = 110.0;
+
+/// Records the totals measured after this frame's physics steps into the history
+pub fn record(
+    mut plot: ResMut<HistoryPlot>,
+    clock: Res<SimulationClock>,
+    diagnostics: Res<DiagnosticsStore>,
+    particles: Query<(), With<Particle>>,
+) {
+    // Nothing new was measured while paused
+    if clock.last_advance() == 0 {
+        return;
+    }
+    let value = |path: &DiagnosticPath| match diagnostics.get(path) {
+        Some(diagnostic) => diagnostic.value().unwrap_or(0.0) as f32,
+        None => 0.0,
+    };
+    plot.history.push(Sample {
+        time: clock.elapsed(),
+        total_energy: value(&Conservation::TOTAL_ENERGY),
+        kinetic_energy: value(&Conservation::KINETIC_ENERGY),
+        potential_energy: value(&Conservation::POTENTIAL_ENERGY),
+        momentum: value(&Conservation::MOMENTUM),
+        particles: particles.iter().count(),
+        step_time: clock.step_time,
+    });
+}
+
+/// Writes the history to its CSV file when requested
+///
+/// Failures are reported in the history window
+pub fn export(mut plot: ResMut<HistoryPlot>) {
+    if !plot.export {
+        return;
+    }
+    plot.export = false;
+    let path = std::path::Path::new(plot.path.trim()).to_path_buf();
+    match plot.history.save_csv(&path) {
+        Ok(()) => {
+            info!("Exported history to '{}'", path.display());
+            plot.error = None;
+        }
+        Err(e) => {
+            warn!("{:#}", e);
+            plot.error = Some(format!("{:#}", e));
+        }
+    }
+}
+
+/// Plots a group of series against simulated time, sharing the time axis with the other plots
+fn plot_series(ui: &mut egui::Ui, id: &str, history: &History, series: &[Series]) {
+    Plot::new(id)
+        .height(PLOT_HEIGHT)
+        .legend(Legend::default())
+        .link_axis("history", true, false)
+        .x_axis_label("Time (s)")
+        .show(ui, |plot_ui| {
+            for s in series {
+                let points = PlotPoints::from(history.series(*s));
+                plot_ui.line(Line::new(points).name(s.label()));
+            }
+        });
+}
+
+/// Shows the history window, with a plot for each kind of quantity
+pub fn window(mut contexts: EguiContexts, plot: ResMut<HistoryPlot>) {
+    if !plot.open {
+        return;
+    }
+    let plot = plot.into_inner();
+    let mut open = true;
+    egui::Window::new("History")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("history_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Window");
+                    ui.add(
+                        egui::DragValue::new(&mut plot.history.window)
+                            .speed(1.0)
+                            .clamp_range(1.0..=3600.0)
+                            .suffix(" s"),
+                    )
+                    .on_hover_text("Simulated time kept, older samples are dropped");
+                    ui.end_row();
+
+                    ui.label("CSV");
+                    ui.text_edit_singleline(&mut plot.path);
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                if ui.button("Export").clicked() {
+                    plot.export = true;
+                }
+                if ui.button("Clear").clicked() {
+                    plot.history.clear();
+                }
+            });
+            if let Some(error) = &plot.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.separator();
+
+            let history = &plot.history;
+            plot_series(
+                ui,
+                "energy_plot",
+                history,
+                &[
+                    Series::TotalEnergy,
+                    Series::KineticEnergy,
+                    Series::PotentialEnergy,
+                ],
+            );
+            plot_series(ui, "momentum_plot", history, &[Series::Momentum]);
+            plot_series(ui, "particles_plot", history, &[Series::Particles]);
+            plot_series(ui, "step_time_plot", history, &[Series::StepTime]);
+        });
+    if !open {
+        plot.open = false;
+    }
+}
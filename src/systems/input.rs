@@ -1,3 +1,5 @@
+use crate::particle::Particle;
+use crate::resources::clock::{SimulationClock, MAX_TIME_SCALE, MIN_TIME_SCALE};
 use crate::resources::input;
 use bevy::{
     input::{mouse::MouseButtonInput, ButtonState},
@@ -6,13 +8,45 @@ use bevy::{
 };
 use bevy_egui::EguiContexts;
 
-fn window2world(window: &Window, camera: &Transform, pos: &Vec2) -> Vec2 {
+/// Converts a position in the window (e.g. the cursor) to a position in the world
+///
+/// ### Arguments
+/// - `window` The window the position is in
+/// - `camera` The camera's transform
+/// - `projection` The camera's projection, its scale is the zoom level
+/// - `pos` The position in window coordinates (origin at the top left, y down)
+pub(crate) fn window2world(
+    window: &Window,
+    camera: &Transform,
+    projection: &OrthographicProjection,
+    pos: &Vec2,
+) -> Vec2 {
     let norm = Vec3::new(
         pos.x - window.width() / 2.,
-        (pos.y - window.height() / 2.) * -1.,
+        -(pos.y - window.height() / 2.),
         0.,
     );
-    (*camera * norm).truncate()
+    (*camera * (norm * projection.scale)).truncate()
+}
+
+/// Finds the particle closest to a point, if the point is on (or within a few pixels of) it
+///
+/// ### Arguments
+/// - `point` The position in the world
+/// - `scale` The camera's projection scale, so the tolerance is the same number of pixels at any zoom
+/// - `particles` The particles to search
+pub(crate) fn particle_at(
+    point: Vec2,
+    scale: f32,
+    particles: &Query<(Entity, &Particle)>,
+) -> Option<Entity> {
+    const TOLERANCE_PX: f32 = 5.0;
+    return particles
+        .iter()
+        .map(|(entity, p)| (entity, p.position().distance(point) - p.radius()))
+        .filter(|(_, gap)| *gap <= TOLERANCE_PX * scale)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
 }
 
 pub fn mouse_hold(
@@ -20,11 +54,11 @@ pub fn mouse_hold(
     mut cursor_moved_events: EventReader<CursorMoved>,
     mut mouse_button_events: EventReader<MouseButtonInput>,
     mut contexts: EguiContexts,
-    camera_transform: Query<&Transform, With<Camera2d>>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
     windows: Query<&Window, With<PrimaryWindow>>,
 ) {
     let window = windows.single();
-    let camera_transform = camera_transform.single();
+    let (camera_transform, projection) = camera.single();
 
     if !contexts.ctx_mut().is_pointer_over_area() {
         for event in mouse_button_events.read() {
@@ -32,16 +66,24 @@ pub fn mouse_hold(
                 MouseButton::Left => {
                     if event.state == ButtonState::Pressed {
                         if let Some(cursor_pos) = window.cursor_position() {
-                            mouse_state.click =
-                                Some(window2world(window, camera_transform, &cursor_pos));
+                            mouse_state.click = Some(window2world(
+                                window,
+                                camera_transform,
+                                projection,
+                                &cursor_pos,
+                            ));
                         }
                         mouse_state.is_held = true;
                         mouse_state.dragging = None;
                         mouse_state.release = None;
                     } else if event.state == ButtonState::Released {
                         if let Some(cursor_pos) = window.cursor_position() {
-                            mouse_state.release =
-                                Some(window2world(window, camera_transform, &cursor_pos));
+                            mouse_state.release = Some(window2world(
+                                window,
+                                camera_transform,
+                                projection,
+                                &cursor_pos,
+                            ));
                         }
                         mouse_state.is_held = false;
                     }
@@ -53,7 +95,40 @@ pub fn mouse_hold(
 
     if mouse_state.is_held {
         for event in cursor_moved_events.read() {
-            mouse_state.dragging = Some(window2world(window, camera_transform, &event.position));
+            mouse_state.dragging = Some(window2world(
+                window,
+                camera_transform,
+                projection,
+                &event.position,
+            ));
         }
     }
 }
+
+/// Keyboard shortcuts for the simulation clock
+///
+/// - `Space` pauses or resumes
+/// - `.` runs a single step (pausing first)
+/// - `[` / `]` halves / doubles the time scale
+pub fn clock_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut clock: ResMut<SimulationClock>,
+    mut contexts: EguiContexts,
+) {
+    // Don't steal keys typed into the GUI's text fields
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Space) {
+        clock.toggle_pause();
+    }
+    if keys.just_pressed(KeyCode::Period) {
+        clock.step_once();
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        clock.time_scale = (clock.time_scale * 0.5).max(MIN_TIME_SCALE);
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        clock.time_scale = (clock.time_scale * 2.0).min(MAX_TIME_SCALE);
+    }
+}
@@ -1,7 +1,17 @@
+pub mod camera;
+pub mod clock;
+pub mod diagnostics;
+pub mod export;
+pub mod field;
 pub mod gui;
+pub mod history;
 pub mod input;
 pub mod particles;
 pub mod path;
+pub mod scenario;
+pub mod selection;
+pub mod snapshot;
+pub mod vectors;
 
 use bevy::prelude::*;
 
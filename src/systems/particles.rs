@@ -1,69 +1,122 @@
+use crate::colormap::{self, ColorMode, ColorScale};
 use crate::error::handle_error;
-use crate::particle::Particle;
+use crate::particle::{BodyColor, Particle, ParticleId};
 use crate::physics;
+use crate::physics::orbit;
 use crate::resources;
 use crate::resources::input;
+use crate::resources::spawn::{SpawnSettings, SpawnTool};
 use crate::utils;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
-// Sizes
-const RAD: f32 = 5.0;
-const BIG_DENSITY: f32 = 1e4; // extremely dense (simulating a sun, large mass)
-const SMALL_DENSITY: f32 = 0.1; // smaller (simulating a planet, smaller mass (relatively))
-const MED_DENSITY: f32 = 1.0; // a larger planet
+// Trajectory preview
+/// Physics steps predicted ahead (each run as the clock's substeps, like the live simulation)
+const PREDICTION_STEPS: usize = 600;
+const PREDICTION_DASH: usize = 4;
+/// Upper bound on the pairwise interactions evaluated by a prediction (the force solver's cost per
+/// substep × substeps), so aiming stays responsive with many particles
+const PREDICTION_BUDGET: usize = 10_000_000;
+/// Fewest substeps predicted, however many particles there are
+const MIN_PREDICTION_STEPS: usize = 60;
+/// Above this many particles the prediction uses Barnes-Hut, whatever the simulation uses
+pub const PREDICTION_BARNES_HUT_ABOVE: usize = 100;
 
-const ORBIT_SPACING: f32 = 50.0;
-const ORBIT_START: f32 = 150.0;
-const NUM_ORBITS: usize = 2;
+#[derive(Component)]
+pub struct SpawnIndicator;
 
-pub fn spawn_initial(mut commands: Commands, state: Res<resources::SimulationState>) {
-    let mut big = Particle::default();
-    handle_error(big.set_radius(RAD));
-    handle_error(big.set_density(BIG_DENSITY));
+/// Everything needed to create the particles spawned with the mouse
+#[derive(SystemParam)]
+pub struct Launcher<'w> {
+    spawn: Res<'w, SpawnSettings>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    clock: Res<'w, resources::clock::SimulationClock>,
+    state: Res<'w, resources::SimulationState>,
+}
 
-    let mut side: f32 = 1.0;
-    for i in 1..NUM_ORBITS + 1 {
+impl Launcher<'_> {
+    /// Creates the particle spawned by dragging from `clicked` to `released`
+    ///
+    /// With `SpawnTool::Orbit` the drag is ignored and the particle is put on an orbit instead
+    fn particle(&self, others: &[Particle], clicked: Vec2, released: Vec2) -> Particle {
         let mut p = Particle::default();
-        p.set_radius(RAD).unwrap();
-        p.set_density(MED_DENSITY).unwrap();
-        let pos = Vec2::new(ORBIT_START * side + ORBIT_SPACING * (i as f32) * side, 0.0);
-        let vel = physics::orbital_velocity(
-            pos,
-            big.position(),
-            big.mass(),
-            state.numeric_constants.g.value,
-        );
-        p.set_pos(pos);
-        p.set_vel(vel);
-        if side == 1.0 {
-            side = -1.0;
-        } else {
-            side = 1.0;
+        handle_error(p.set_radius(self.spawn.radius));
+        handle_error(p.set_density(self.spawn.density));
+        p.set_pos(clicked);
+        match self.spawn.tool {
+            SpawnTool::Launch => p.set_vel(clicked - released),
+            SpawnTool::Orbit => {
+                if let Some(i) = self.spawn.target.find(others, clicked) {
+                    let reverse = self.reversed();
+                    p.set_vel(orbit::periapsis_velocity(
+                        clicked,
+                        p.mass(),
+                        &others[i],
+                        self.state.settings.constants.g.value,
+                        self.spawn.eccentricity,
+                        self.spawn.prograde != reverse,
+                    ));
+                }
+            }
         }
-        commands.spawn(p.bundle(Color::WHITE, None));
+        return p;
+    }
+
+    /// Whether orbits are reversed (Shift is held)
+    fn reversed(&self) -> bool {
+        return self
+            .keys
+            .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
     }
 
-    commands.spawn(big.bundle(Color::RED, None));
+    /// Predicts where a particle would go if it was spawned now
+    ///
+    /// Steps with the same substep as the live simulation. The horizon is shortened, and
+    /// Barnes-Hut used, when there are enough particles that a full prediction would stall a frame
+    fn predict(&self, others: &[Particle], p: Particle) -> Vec<Vec2> {
+        let n = others.len() + 1;
+        let mut settings = self.state.settings.clone();
+        if n > PREDICTION_BARNES_HUT_ABOVE {
+            settings.force_solver = physics::ForceSolver::BarnesHut;
+        }
+        let cost = match settings.force_solver {
+            physics::ForceSolver::Direct => n * n,
+            physics::ForceSolver::BarnesHut => n * (n.ilog2() as usize + 1),
+        };
+        let steps = (PREDICTION_STEPS * self.clock.substeps.max(1))
+            .min((PREDICTION_BUDGET / cost).max(MIN_PREDICTION_STEPS));
+        return settings.predict(others, p, self.clock.substep_dt(), steps);
+    }
 }
 
-#[derive(Component)]
-pub struct SpawnIndicator;
+/// The last predicted trajectory, and what it was predicted from
+#[derive(Default)]
+pub struct Prediction {
+    clicked: Vec2,
+    drag: Vec2,
+    reversed: bool,
+    particles: usize,
+    points: Vec<Vec2>,
+}
 
 pub fn spawn_input(
     mut commands: Commands,
+    mut prediction: Local<Prediction>,
     mut mouse_state: ResMut<input::MouseState>,
     spawn_indicators: Query<Entity, With<SpawnIndicator>>,
+    particles: Query<&Particle>,
+    launcher: Launcher,
 ) {
+    let others: Vec<Particle> = particles.iter().cloned().collect();
     if let Some(released) = mouse_state.release {
         if let Some(clicked) = mouse_state.click {
-            let mut p = Particle::default();
-            handle_error(p.set_radius(RAD));
-            handle_error(p.set_density(SMALL_DENSITY));
-            p.set_pos(clicked);
-            let vel = clicked - released;
-            p.set_vel(vel);
-            commands.spawn(p.bundle(Color::WHITE, None));
+            let p = launcher.particle(&others, clicked, released);
+            let controls = &launcher.state.controls;
+            commands.spawn(p.bundle(
+                controls.particle_color,
+                Some(Stroke::new(controls.particle_stroke, 1.0)),
+            ));
             *mouse_state = input::MouseState::default();
         }
     }
@@ -72,64 +125,111 @@ pub fn spawn_input(
         commands.entity(entity).despawn();
     }
 
-    if let Some(drag) = mouse_state.dragging {
-        if let None = mouse_state.release {
-            if let Some(clicked) = mouse_state.click {
+    if mouse_state.is_held && mouse_state.release.is_none() {
+        if let Some(clicked) = mouse_state.click {
+            let drag = mouse_state.dragging.unwrap_or(clicked);
+            if launcher.spawn.tool == SpawnTool::Launch {
                 commands.spawn((
                     utils::LineBundle::new(clicked, drag, Color::WHITE, 1.0),
                     SpawnIndicator,
                 ));
             }
+
+            // Where the particle would go if it was released now, only predicted again when the
+            // drag, the particles or the spawn settings change, or the other particles have moved
+            let stale = prediction.points.is_empty()
+                || launcher.clock.last_advance() > 0
+                || prediction.clicked != clicked
+                || prediction.drag != drag
+                || prediction.reversed != launcher.reversed()
+                || prediction.particles != others.len()
+                || launcher.spawn.is_changed();
+            if stale {
+                *prediction = Prediction {
+                    clicked,
+                    drag,
+                    reversed: launcher.reversed(),
+                    particles: others.len(),
+                    points: launcher.predict(&others, launcher.particle(&others, clicked, drag)),
+                };
+            }
+            if prediction.points.len() > 1 {
+                commands.spawn((
+                    utils::LineBundle::dashed(
+                        &prediction.points,
+                        PREDICTION_DASH,
+                        Color::rgba(1.0, 1.0, 1.0, 0.6),
+                        1.0,
+                    ),
+                    SpawnIndicator,
+                ));
+            }
         }
+    } else {
+        // The next drag starts from a fresh prediction
+        prediction.points.clear();
     }
 }
-const SUBSTEPS: usize = 1;
+
 pub fn update(
-    mut query: Query<(Entity, &mut Particle)>,
-    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Path)>,
+    clock: Res<resources::clock::SimulationClock>,
     state: Res<resources::SimulationState>,
 ) {
-    let particles: Vec<(Entity, Particle)> = query.iter().map(|(e, p)| (e, p.clone())).collect();
-    let dt = time.delta_seconds();
-    let sub_dt = dt / (SUBSTEPS as f32);
-    for _ in 0..SUBSTEPS {
-        for (entity_a, mut a) in query.iter_mut() {
-            for (entity_b, b) in &particles {
-                if entity_a == *entity_b {
-                    continue;
-                }
-                if physics::is_intersecting(&a, b) {
-                    physics::resolve_intersection(&mut a, b);
-                    physics::resolve_collision(
-                        &mut a,
-                        b,
-                        state.numeric_constants.restitution.value,
-                    );
-                }
-                physics::attract(&mut a, b, state.numeric_constants.g.value);
+    let (mut entities, mut particles): (Vec<Entity>, Vec<Particle>) =
+        query.iter().map(|(e, p, _)| (e, p.clone())).unzip();
+    let sub_dt = clock.substep_dt();
+    for _ in 0..clock.substeps.max(1) {
+        let merges = state.settings.step(&mut particles, sub_dt);
+        for merge in &merges {
+            commands.entity(entities[merge.absorbed]).despawn();
+        }
+        physics::remove_absorbed(&mut entities, &merges);
+    }
+
+    for (entity, p) in entities.into_iter().zip(particles) {
+        if let Ok((_, mut particle, mut shape)) = query.get_mut(entity) {
+            // Merged particles grow, so their circle needs to be rebuilt
+            if particle.radius() != p.radius() {
+                *shape = p.shape();
             }
-            physics::update_particle(&mut a, sub_dt);
+            *particle = p;
         }
     }
 }
 
-pub fn render(mut query: Query<(&mut Transform, &Particle, &mut Fill)>) {
-    let mut min_mass = f32::MAX;
-    let mut max_mass = f32::MIN;
-    for (_, particle, _) in query.iter() {
-        min_mass = min_mass.min(particle.mass());
-        max_mass = max_mass.max(particle.mass());
-    }
-    for (mut transform, particle, mut fill) in query.iter_mut() {
-        let pos = particle.position();
-        transform.translation = pos.extend(0.0);
-        
-        // Set the fill color according to mass (most massive is black, least massive is white)
-        let normalized_mass = utils::math::normalize(particle.mass(), min_mass, max_mass, 0.0, 1.0);
-        *fill = Fill::color(Color::rgb(
-                1.0 - normalized_mass,
-                1.0 - normalized_mass,
-                1.0 - normalized_mass,
-        ));
+/// Moves each particle's shape to its position and colours it by the selected `ColorMode`
+///
+/// Particles spawned this frame have no `ParticleId` yet, so keep their assigned colour until they
+/// are given one
+pub fn render(
+    state: Res<resources::SimulationState>,
+    mut query: Query<(
+        &mut Transform,
+        &Particle,
+        &mut Fill,
+        &BodyColor,
+        Option<&ParticleId>,
+    )>,
+) {
+    let controls = &state.controls;
+    let scale = ColorScale::new(controls.color_mode, query.iter().map(|(_, p, _, _, _)| p));
+    for (mut transform, particle, mut fill, assigned, id) in query.iter_mut() {
+        transform.translation = particle.position().extend(0.0);
+
+        let color = match (controls.color_mode, scale) {
+            (ColorMode::Assigned, _) => assigned.0,
+            (ColorMode::Id, _) => match id {
+                Some(id) => Color::hsl(colormap::hue(id.0), 0.75, 0.6),
+                None => assigned.0,
+            },
+            (_, Some(scale)) => {
+                let [r, g, b] = controls.colormap.sample(scale.position(particle));
+                Color::rgb(r, g, b)
+            }
+            (_, None) => assigned.0,
+        };
+        *fill = Fill::color(color);
     }
 }
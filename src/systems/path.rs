@@ -1,46 +1,177 @@
+use crate::frame::{self, FramePoint};
 use crate::particle;
+use crate::physics;
 use crate::resources;
-use crate::utils;
+use crate::resources::frame::{ReferenceFrame, TrailFrame};
 use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::NoFrustumCulling;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy_prototype_lyon::prelude::Fill;
 
+/// Opacity of the newest end of a trail, the oldest end fades to nothing
+const TRAIL_ALPHA: f32 = 0.6;
+
+/// Records each particle's position, and the centre of mass, into their trails
 pub fn update(
     mut query: Query<(&mut particle::path::Path, &particle::Particle)>,
+    mut trail_frame: ResMut<TrailFrame>,
     state: Res<resources::SimulationState>,
+    clock: Res<resources::clock::SimulationClock>,
 ) {
-    if state.controls.show_path {
+    let controls = &state.controls;
+    let center_of_mass = &mut trail_frame.center_of_mass;
+    if controls.show_path {
+        if center_of_mass.capacity() != controls.trail_length {
+            center_of_mass.set_capacity(controls.trail_length);
+        }
+        for (mut path, _) in query.iter_mut() {
+            if path.capacity() != controls.trail_length {
+                path.set_capacity(controls.trail_length);
+            }
+        }
+        // Only record a point when the particles have moved, so pausing doesn't pile up points
+        if clock.last_advance() == 0 {
+            return;
+        }
+        let particles: Vec<particle::Particle> = query.iter().map(|(_, p)| p.clone()).collect();
+        if let Some(com) = physics::center_of_mass(&particles) {
+            center_of_mass.add_point(com);
+        }
         for (mut path, particle) in query.iter_mut() {
             path.add_point(particle.position());
         }
     } else {
+        center_of_mass.reset();
         for (mut path, _) in query.iter_mut() {
             path.reset();
         }
     }
 }
 
+/// The single mesh every trail is drawn into
 #[derive(Component)]
 pub struct ParticlePath;
 
-pub fn render(
+/// Creates the trail mesh, drawn behind the particles
+pub fn setup(
     mut commands: Commands,
-    query: Query<&particle::path::Path>,
-    particle_paths: Query<Entity, With<ParticlePath>>,
-    state: Res<resources::SimulationState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for entity in particle_paths.iter() {
-        commands.entity(entity).despawn();
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(trail_mesh(Vec::new(), Vec::new())).into(),
+            material: materials.add(ColorMaterial::from(Color::WHITE)),
+            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+            ..default()
+        },
+        // The bounds change every frame, so they are never calculated
+        NoFrustumCulling,
+        ParticlePath,
+    ));
+}
+
+fn trail_mesh(mut positions: Vec<[f32; 3]>, mut colors: Vec<[f32; 4]>) -> Mesh {
+    if positions.is_empty() {
+        // A single invisible segment, so the vertex buffers are never empty
+        positions = vec![[0.0; 3]; 2];
+        colors = vec![[0.0; 4]; 2];
     }
+    return Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
 
-    if state.controls.show_path {
-        for path in query.iter() {
-            if path.size() < 2 {
-                continue;
+/// Gets the history of the frame trails are drawn in, `None` for the inertial frame
+///
+/// Falls back to the inertial frame if a particle the frame follows has been removed
+fn frame_history(
+    trail_frame: &mut TrailFrame,
+    query: &Query<(&particle::path::Path, &particle::Particle, &Fill)>,
+) -> Option<Vec<FramePoint>> {
+    let history = match trail_frame.frame {
+        ReferenceFrame::Inertial => return None,
+        ReferenceFrame::CenterOfMass => Some(
+            trail_frame
+                .center_of_mass
+                .points
+                .iter()
+                .map(|p| FramePoint::translating(*p))
+                .collect(),
+        ),
+        ReferenceFrame::Particle(entity) => query.get(entity).ok().map(|(path, _, _)| {
+            path.points
+                .iter()
+                .map(|p| FramePoint::translating(*p))
+                .collect()
+        }),
+        ReferenceFrame::CoRotating(a, b) => match (query.get(a), query.get(b)) {
+            (Ok((path_a, p_a, _)), Ok((path_b, p_b, _))) => {
+                // Aligned at the newest points, as the trails were all recorded together
+                let n = path_a.size().min(path_b.size());
+                let points_a = path_a.points.iter().skip(path_a.size() - n);
+                let points_b = path_b.points.iter().skip(path_b.size() - n);
+                Some(
+                    points_a
+                        .zip(points_b)
+                        .map(|(a, b)| FramePoint::corotating(*a, p_a.mass(), *b, p_b.mass()))
+                        .collect(),
+                )
             }
+            _ => None,
+        },
+    };
+    if history.is_none() {
+        trail_frame.frame = ReferenceFrame::Inertial;
+    }
+    return history;
+}
 
-            commands.spawn((
-                utils::LineBundle::path(&path.points, Color::rgba(1.0, 1.0, 1.0, 0.5), 1.0),
-                ParticlePath,
-            ));
+/// Rebuilds the trail mesh in place from every particle's path, projected into the trail frame
+///
+/// Each trail is the particle's colour, fading out towards its oldest point
+pub fn render(
+    query: Query<(&particle::path::Path, &particle::Particle, &Fill)>,
+    mut trail_frame: ResMut<TrailFrame>,
+    trails: Query<&Mesh2dHandle, With<ParticlePath>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok(handle) = trails.get_single() else {
+        return;
+    };
+    let Some(mesh) = meshes.get_mut(&handle.0) else {
+        return;
+    };
+    let history = frame_history(&mut trail_frame, &query);
+
+    let segments: usize = query
+        .iter()
+        .map(|(path, _, _)| path.size().saturating_sub(1))
+        .sum();
+    let mut positions = Vec::with_capacity(segments * 2);
+    let mut colors = Vec::with_capacity(segments * 2);
+    for (path, _, fill) in query.iter() {
+        let points = match &history {
+            Some(history) => frame::project(path, history),
+            None => path.points.iter().copied().collect(),
+        };
+        let n = points.len();
+        if n < 2 {
+            continue;
+        }
+        let [r, g, b, _] = fill.color.as_linear_rgba_f32();
+        let vertex = |i: usize, point: Vec2| {
+            let alpha = TRAIL_ALPHA * i as f32 / (n - 1) as f32;
+            ([point.x, point.y, 0.0], [r, g, b, alpha])
+        };
+        for (i, pair) in points.windows(2).enumerate() {
+            for (position, color) in [vertex(i, pair[0]), vertex(i + 1, pair[1])] {
+                positions.push(position);
+                colors.push(color);
+            }
         }
     }
+    *mesh = trail_mesh(positions, colors);
 }
@@ -0,0 +1,86 @@
+use crate::particle::Particle;
+use crate::resources;
+use crate::resources::clock::SimulationClock;
+use crate::resources::constants::NumericConstants;
+use crate::resources::scenario::{ScenarioFile, ScenarioRequest};
+use crate::scenario::Scenario;
+use crate::systems::diagnostics::ConservationBaseline;
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use std::path::Path;
+
+/// Used when no scenario file is given
+const DEFAULT_SCENARIO: &str = include_str!("../../scenarios/default.ron");
+
+/// Replaces every particle with the bodies of the scenario, generator or preset, when requested
+///
+/// Constants left out of a scenario file go back to their defaults. Generators and presets are
+/// built for the current constants, so only the constants they set are changed
+///
+/// Invalid scenarios leave the current simulation running and report the error in the GUI
+pub fn load(
+    mut commands: Commands,
+    mut file: ResMut<ScenarioFile>,
+    mut state: ResMut<resources::SimulationState>,
+    mut clock: ResMut<SimulationClock>,
+    mut baseline: ResMut<ConservationBaseline>,
+    particles: Query<Entity, With<Particle>>,
+) {
+    let Some(request) = file.request.take() else {
+        return;
+    };
+
+    let g = state.settings.constants.g.value;
+    let mut constants = match request {
+        ScenarioRequest::File => NumericConstants::new(),
+        _ => state.settings.constants.clone(),
+    };
+    let scenario = match request {
+        ScenarioRequest::Generator => {
+            let softening = state.settings.constants.softening.value;
+            let particles = file.generator.generate(g, softening);
+            Ok(Scenario::from_particles(
+                file.generator.kind.label(),
+                &particles,
+            ))
+        }
+        ScenarioRequest::Preset => Ok(file.preset.scenario(g)),
+        ScenarioRequest::File if file.path.trim().is_empty() => {
+            Scenario::from_ron(DEFAULT_SCENARIO)
+        }
+        ScenarioRequest::File => Scenario::load(Path::new(file.path.trim())),
+    };
+    let loaded = scenario.and_then(|scenario| {
+        let bodies = scenario.particles()?;
+        scenario.constants.apply(&mut constants)?;
+        return Ok((scenario, bodies));
+    });
+    let (scenario, bodies) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            warn!("{:#}", e);
+            file.error = Some(format!("{:#}", e));
+            return;
+        }
+    };
+    file.error = None;
+    state.settings.constants = constants;
+
+    for entity in particles.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (body, p) in scenario.bodies.iter().zip(bodies) {
+        let color = match body.color {
+            Some([r, g, b, a]) => Color::rgba(r, g, b, a),
+            None => state.controls.particle_color,
+        };
+        let stroke = Stroke::new(state.controls.particle_stroke, 1.0);
+        let mut entity = commands.spawn(p.bundle(color, Some(stroke)));
+        if let Some(name) = &body.name {
+            entity.insert(Name::new(name.clone()));
+        }
+    }
+    clock.reset();
+    baseline.initial = None;
+    info!("Loaded scenario '{}'", scenario.name);
+}
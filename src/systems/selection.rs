@@ -0,0 +1,320 @@
+use crate::particle::{Particle, ParticleId};
+use crate::physics::orbit::{self, OrbitalElements};
+use crate::resources;
+use crate::resources::camera::{CameraControls, CameraFollow};
+use crate::resources::frame::{ReferenceFrame, TrailFrame};
+use crate::resources::input;
+use crate::resources::selection::{MassEdit, Selection};
+use crate::systems::diagnostics::ConservationBaseline;
+use crate::systems::input::{particle_at, window2world};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+use bevy_prototype_lyon::prelude::*;
+
+const HIGHLIGHT_COLOR: Color = Color::YELLOW;
+const HIGHLIGHT_WIDTH: f32 = 1.5;
+
+/// Selects the particle under the cursor when it is left clicked
+///
+/// Runs after `systems::input::mouse_hold` and cancels the spawn it started, so clicking a particle
+/// never spawns another on top of it. Clicking empty space spawns as usual
+pub fn pick(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut contexts: EguiContexts,
+    mut mouse_state: ResMut<input::MouseState>,
+    mut selection: ResMut<Selection>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    particles: Query<(Entity, &Particle)>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+    let window = windows.single();
+    let (transform, projection) = camera.single();
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let cursor = window2world(window, transform, projection, &cursor);
+    if let Some(entity) = particle_at(cursor, projection.scale, &particles) {
+        selection.entity = Some(entity);
+        *mouse_state = input::MouseState::default();
+    }
+}
+
+/// Outlines the selected particle, every other particle gets the outline from the controls
+pub fn highlight(
+    selection: Res<Selection>,
+    state: Res<resources::SimulationState>,
+    mut strokes: Query<(Entity, &mut Stroke), With<Particle>>,
+) {
+    for (entity, mut stroke) in strokes.iter_mut() {
+        let new = if selection.entity == Some(entity) {
+            Stroke::new(HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH)
+        } else {
+            Stroke::new(state.controls.particle_stroke, 1.0)
+        };
+        // Only write changes, so the shapes aren't rebuilt every frame
+        if stroke.color != new.color || stroke.options.line_width != new.options.line_width {
+            *stroke = new;
+        }
+    }
+}
+
+fn vec2_row(ui: &mut egui::Ui, label: &str, value: &mut Vec2, speed: f32) -> bool {
+    ui.label(label);
+    let changed = ui
+        .horizontal(|ui| {
+            let x = ui.add(
+                egui::DragValue::new(&mut value.x)
+                    .speed(speed)
+                    .prefix("x: "),
+            );
+            let y = ui.add(
+                egui::DragValue::new(&mut value.y)
+                    .speed(speed)
+                    .prefix("y: "),
+            );
+            x.changed() || y.changed()
+        })
+        .inner;
+    ui.end_row();
+    return changed;
+}
+
+fn orbit_section(ui: &mut egui::Ui, elements: &OrbitalElements) {
+    let optional = |value: Option<f32>| match value {
+        Some(value) => format!("{:.2}", value),
+        None => "unbound".to_string(),
+    };
+    egui::Grid::new("orbit_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            let labels = vec![
+                (
+                    "Semi-major Axis:",
+                    format!("{:.2}", elements.semi_major_axis),
+                ),
+                ("Eccentricity:", format!("{:.4}", elements.eccentricity)),
+                ("Periapsis:", format!("{:.2}", elements.periapsis)),
+                ("Apoapsis:", optional(elements.apoapsis)),
+                ("Period:", optional(elements.period)),
+                (
+                    "Arg. of Periapsis:",
+                    format!("{:.1}°", elements.argument_of_periapsis.to_degrees()),
+                ),
+                (
+                    "Direction:",
+                    if elements.prograde {
+                        "Anti-clockwise".to_string()
+                    } else {
+                        "Clockwise".to_string()
+                    },
+                ),
+            ];
+            for (label, value) in labels {
+                ui.label(label);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+/// A particle, as the inspector edits it
+type Inspected = (
+    Entity,
+    &'static mut Particle,
+    &'static mut Path,
+    Option<&'static Name>,
+    Option<&'static ParticleId>,
+);
+
+/// Gets the name a particle is shown with: its scenario name, or its ID
+fn particle_label(name: Option<&Name>, id: Option<&ParticleId>) -> String {
+    return match (name, id) {
+        (Some(name), _) => name.to_string(),
+        (None, Some(id)) => format!("Particle #{}", id.0),
+        (None, None) => "New particle".to_string(),
+    };
+}
+
+/// Shows the selected particle's properties and lets them be edited in place
+pub fn inspector(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<Selection>,
+    mut camera: ResMut<CameraControls>,
+    mut trail_frame: ResMut<TrailFrame>,
+    mut baseline: ResMut<ConservationBaseline>,
+    state: Res<resources::SimulationState>,
+    mut particles: Query<Inspected>,
+) {
+    let Some(selected) = selection.entity else {
+        return;
+    };
+    let (entities, all): (Vec<Entity>, Vec<Particle>) =
+        particles.iter().map(|(e, p, ..)| (e, p.clone())).unzip();
+    let labels: Vec<String> = particles
+        .iter()
+        .map(|(_, _, _, name, id)| particle_label(name, id))
+        .collect();
+    let Some(index) = entities.iter().position(|e| *e == selected) else {
+        // Despawned, e.g. absorbed by a merge
+        selection.entity = None;
+        return;
+    };
+    let g = state.settings.constants.g.value;
+    let dominant = orbit::dominant_body(&all, index);
+    let elements = dominant.and_then(|j| OrbitalElements::new(&all[index], &all[j], g));
+
+    let mut p = all[index].clone();
+    let mut pos = p.position();
+    let mut vel = p.velocity();
+    let mut radius = p.radius();
+    let mut density = p.density();
+    let mut mass = p.mass();
+    let mut mass_edit = selection.mass_edit;
+    let mut open = true;
+    let mut edited = false;
+    let mut follow = false;
+    let mut frame = None;
+
+    egui::Window::new("Inspector")
+        .open(&mut open)
+        .resizable([true, false])
+        .default_width(260.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("inspector_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Particle:");
+                    ui.label(&labels[index]);
+                    ui.end_row();
+
+                    if vec2_row(ui, "Position", &mut pos, 1.0) {
+                        p.set_pos(pos);
+                        edited = true;
+                    }
+                    if vec2_row(ui, "Velocity", &mut vel, 1.0) {
+                        p.set_vel(vel);
+                        edited = true;
+                    }
+
+                    ui.label("Acceleration:");
+                    let acc = p.acceleration();
+                    ui.label(format!("({:.2}, {:.2})", acc.x, acc.y));
+                    ui.end_row();
+
+                    ui.label("Radius");
+                    let drag = egui::DragValue::new(&mut radius)
+                        .speed(0.1)
+                        .clamp_range(0.1..=500.0);
+                    if ui.add(drag).changed() && p.set_radius(radius).is_ok() {
+                        edited = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Density");
+                    let speed = density.max(1e-3) * 0.01;
+                    let drag = egui::DragValue::new(&mut density)
+                        .speed(speed)
+                        .clamp_range(1e-6..=f32::MAX);
+                    if ui.add(drag).changed() && p.set_density(density).is_ok() {
+                        edited = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Mass");
+                    let speed = mass.max(1e-3) * 0.01;
+                    let drag = egui::DragValue::new(&mut mass)
+                        .speed(speed)
+                        .clamp_range(1e-6..=f32::MAX);
+                    if ui.add(drag).changed() {
+                        match mass_edit {
+                            MassEdit::KeepRadius => p.set_mass_with_density(mass),
+                            MassEdit::KeepDensity => p.set_mass_with_radius(mass),
+                        }
+                        edited = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Mass Changes");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut mass_edit, MassEdit::KeepRadius, "Density");
+                        ui.radio_value(&mut mass_edit, MassEdit::KeepDensity, "Radius");
+                    });
+                    ui.end_row();
+
+                    ui.label("Kinetic Energy:");
+                    ui.label(format!(
+                        "{:.4e}",
+                        0.5 * p.mass() * p.velocity().length_squared()
+                    ));
+                    ui.end_row();
+                });
+
+            ui.separator();
+            match (dominant, elements) {
+                (Some(j), Some(elements)) => {
+                    ui.label(format!("Orbit about {}", labels[j]));
+                    orbit_section(ui, &elements);
+                }
+                _ => {
+                    ui.label("No orbit");
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Follow").clicked() {
+                    follow = true;
+                }
+                if ui
+                    .button("Trails Relative")
+                    .on_hover_text("Draw trails in a frame moving with this particle")
+                    .clicked()
+                {
+                    frame = Some(ReferenceFrame::Particle(selected));
+                }
+                if let Some(j) = dominant {
+                    if ui
+                        .button("Co-rotate")
+                        .on_hover_text(format!(
+                            "Draw trails in a frame rotating with this particle and {}",
+                            labels[j]
+                        ))
+                        .clicked()
+                    {
+                        frame = Some(ReferenceFrame::CoRotating(entities[j], selected));
+                    }
+                }
+            });
+        });
+
+    if mass_edit != selection.mass_edit {
+        selection.mass_edit = mass_edit;
+    }
+    if follow {
+        camera.follow = CameraFollow::Particle(selected);
+    }
+    if let Some(frame) = frame {
+        trail_frame.frame = frame;
+    }
+    if !open {
+        selection.entity = None;
+    }
+    if edited {
+        if let Ok((_, mut particle, mut shape, ..)) = particles.get_mut(selected) {
+            if particle.radius() != p.radius() {
+                *shape = p.shape();
+            }
+            *particle = p;
+        }
+        // The totals have changed, so drifts are measured from the edited state
+        baseline.initial = None;
+    }
+}
@@ -0,0 +1,134 @@
+use crate::particle::path::Path;
+use crate::particle::{BodyColor, Particle, ParticleId};
+use crate::resources;
+use crate::resources::clock::SimulationClock;
+use crate::resources::export::NextParticleId;
+use crate::resources::frame::TrailFrame;
+use crate::resources::snapshot::{SnapshotFile, SnapshotRequest};
+use crate::snapshot::{ClockState, ControlsState, ParticleState, Snapshot};
+use crate::systems::diagnostics::ConservationBaseline;
+use anyhow::Result;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+/// The resources a snapshot saves and restores, besides the particles
+#[derive(SystemParam)]
+pub struct SnapshotResources<'w> {
+    state: ResMut<'w, resources::SimulationState>,
+    clock: ResMut<'w, SimulationClock>,
+    baseline: ResMut<'w, ConservationBaseline>,
+    next_id: ResMut<'w, NextParticleId>,
+    trail_frame: ResMut<'w, TrailFrame>,
+}
+
+/// Saves the simulation to, or restores it from, the snapshot file when requested
+///
+/// Failures leave the current simulation running and report the error in the GUI
+pub fn save_load(
+    mut commands: Commands,
+    mut file: ResMut<SnapshotFile>,
+    mut res: SnapshotResources,
+    particles: Query<(Entity, &Particle, &Path, &BodyColor, Option<&ParticleId>)>,
+) {
+    let Some(request) = file.request.take() else {
+        return;
+    };
+    let path = std::path::Path::new(file.path.trim()).to_path_buf();
+    let result = match request {
+        SnapshotRequest::Save => save(&path, &res, &particles),
+        SnapshotRequest::Load => Snapshot::load(&path).and_then(|snapshot| {
+            let SnapshotResources {
+                state,
+                clock,
+                baseline,
+                next_id,
+                trail_frame,
+            } = &mut res;
+            let bodies = snapshot.particles()?;
+            state.settings = snapshot.settings()?;
+            if let Some(controls) = &snapshot.controls {
+                controls.apply(&mut state.controls)?;
+            }
+            if let Some(saved) = &snapshot.clock {
+                saved.apply(clock);
+            }
+            clock.restore(snapshot.steps, snapshot.time);
+            baseline.initial = None;
+
+            for (entity, _, _, _, _) in particles.iter() {
+                commands.entity(entity).despawn();
+            }
+            let controls = &state.controls;
+            let stroke = Stroke::new(controls.particle_stroke, 1.0);
+            let (ids, next) = snapshot.ids();
+            for ((saved, p), id) in snapshot.particles.iter().zip(bodies).zip(ids) {
+                commands
+                    .spawn(p.bundle(
+                        saved.color.map_or(controls.particle_color, color),
+                        Some(stroke),
+                    ))
+                    .insert((saved.path(), ParticleId(id)));
+            }
+            next_id.0 = next;
+            let mut center_of_mass = Path::new(controls.trail_length);
+            for point in &snapshot.center_of_mass_path {
+                center_of_mass.add_point(*point);
+            }
+            trail_frame.center_of_mass = center_of_mass;
+            return Ok(());
+        }),
+    };
+    match result {
+        Ok(()) => {
+            info!("{:?} snapshot '{}'", request, path.display());
+            file.error = None;
+        }
+        Err(e) => {
+            warn!("{:#}", e);
+            file.error = Some(format!("{:#}", e));
+        }
+    }
+}
+
+fn save(
+    path: &std::path::Path,
+    res: &SnapshotResources,
+    particles: &Query<(Entity, &Particle, &Path, &BodyColor, Option<&ParticleId>)>,
+) -> Result<()> {
+    let (state, clock) = (&res.state, &res.clock);
+    let mut snapshot = Snapshot::new(&[], &state.settings, clock.elapsed(), clock.steps());
+    // Particles spawned this frame have no ID yet, they get the ones they are about to be given
+    let mut next_id = res.next_id.0;
+    snapshot.particles = particles
+        .iter()
+        .map(|(_, p, path, color, id)| {
+            let id = match id {
+                Some(id) => id.0,
+                None => {
+                    next_id += 1;
+                    next_id - 1
+                }
+            };
+            return ParticleState {
+                color: Some(color.0.as_rgba_f32()),
+                ..ParticleState::with_path(id, p, path)
+            };
+        })
+        .collect();
+    snapshot.next_id = next_id;
+    snapshot.center_of_mass_path = res
+        .trail_frame
+        .center_of_mass
+        .points
+        .iter()
+        .copied()
+        .collect();
+    snapshot.clock = Some(ClockState::new(clock));
+    snapshot.controls = Some(ControlsState::new(&state.controls));
+    return snapshot.save(path);
+}
+
+fn color([r, g, b, a]: [f32; 4]) -> Color {
+    return Color::rgba(r, g, b, a);
+}
@@ -0,0 +1,96 @@
+use crate::particle::Particle;
+use crate::resources;
+use crate::utils;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_prototype_lyon::prelude::*;
+
+const VELOCITY_COLOR: Color = Color::rgb(0.3, 0.9, 0.4);
+const ACCELERATION_COLOR: Color = Color::rgb(1.0, 0.45, 0.2);
+
+/// Which of a particle's vectors an arrow shows
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum VectorKind {
+    Velocity,
+    Acceleration,
+}
+
+impl VectorKind {
+    const ALL: [VectorKind; 2] = [VectorKind::Velocity, VectorKind::Acceleration];
+
+    fn color(&self) -> Color {
+        match self {
+            VectorKind::Velocity => VELOCITY_COLOR,
+            VectorKind::Acceleration => ACCELERATION_COLOR,
+        }
+    }
+
+    /// Gets the vector scaled to the arrow's length, `None` if its arrows are hidden
+    fn arrow(&self, p: &Particle, controls: &resources::controls::Controls) -> Option<Vec2> {
+        match self {
+            VectorKind::Velocity if controls.show_velocity => {
+                Some(p.velocity() * controls.velocity_scale)
+            }
+            VectorKind::Acceleration if controls.show_acceleration => {
+                Some(p.acceleration() * controls.acceleration_scale)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An arrow drawn from a particle
+#[derive(Component)]
+pub struct VectorArrow {
+    particle: Entity,
+    kind: VectorKind,
+}
+
+/// Draws the velocity and acceleration arrows that are turned on
+///
+/// Each arrow is spawned once and its shape is replaced every frame. Arrows are only despawned
+/// when they are turned off or their particle is removed
+pub fn update(
+    mut commands: Commands,
+    state: Res<resources::SimulationState>,
+    particles: Query<(Entity, &Particle)>,
+    mut arrows: Query<(Entity, &VectorArrow, &mut Path)>,
+) {
+    let controls = &state.controls;
+    let mut drawn = HashSet::new();
+    for (entity, arrow, mut path) in arrows.iter_mut() {
+        let vector = particles
+            .get(arrow.particle)
+            .ok()
+            .and_then(|(_, p)| arrow.kind.arrow(p, controls).map(|v| (p.position(), v)));
+        match vector {
+            Some((start, vector)) => {
+                *path = utils::arrow_path(start, start + vector);
+                drawn.insert((arrow.particle, arrow.kind));
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (entity, p) in particles.iter() {
+        for kind in VectorKind::ALL {
+            if drawn.contains(&(entity, kind)) {
+                continue;
+            }
+            if let Some(vector) = kind.arrow(p, controls) {
+                commands.spawn((
+                    utils::LineBundle::arrow(
+                        p.position(),
+                        p.position() + vector,
+                        kind.color(),
+                        1.0,
+                    ),
+                    VectorArrow {
+                        particle: entity,
+                        kind,
+                    },
+                ));
+            }
+        }
+    }
+}
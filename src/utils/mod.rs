@@ -1,13 +1,17 @@
 pub mod math;
+#[cfg(feature = "app")]
 use bevy::prelude::*;
+#[cfg(feature = "app")]
 use bevy_prototype_lyon::prelude::*;
 
+#[cfg(feature = "app")]
 #[derive(Bundle)]
 pub struct LineBundle {
     shape_bundle: ShapeBundle,
     stroke: Stroke,
 }
 
+#[cfg(feature = "app")]
 impl LineBundle {
     pub fn new(start: Vec2, end: Vec2, color: Color, stroke: f32) -> Self {
         Self {
@@ -35,4 +39,83 @@ impl LineBundle {
             stroke: Stroke::new(color, stroke),
         };
     }
+
+    /// An arrow from `start` to `end`, drawn above the particles
+    pub fn arrow(start: Vec2, end: Vec2, color: Color, stroke: f32) -> Self {
+        return Self {
+            shape_bundle: ShapeBundle {
+                path: arrow_path(start, end),
+                spatial: SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 1.0)),
+                ..default()
+            },
+            stroke: Stroke::new(color, stroke),
+        };
+    }
+
+    /// A dashed polyline, alternating between `dash` segments drawn and `dash` segments skipped
+    pub fn dashed(points: &[Vec2], dash: usize, color: Color, stroke: f32) -> Self {
+        let dash = dash.max(1);
+        let mut builder = PathBuilder::new();
+        for (i, segment) in points.windows(2).enumerate() {
+            // Odd dashes are the gaps
+            if (i / dash) % 2 == 1 {
+                continue;
+            }
+            if i % dash == 0 {
+                builder.move_to(segment[0]);
+            }
+            builder.line_to(segment[1]);
+        }
+
+        let path = builder.build();
+        return Self {
+            shape_bundle: ShapeBundle { path, ..default() },
+            stroke: Stroke::new(color, stroke),
+        };
+    }
+}
+
+/// Length of an arrow's head as a fraction of the arrow's length
+#[cfg(feature = "app")]
+const ARROW_HEAD: f32 = 0.25;
+/// Longest an arrow's head can be
+#[cfg(feature = "app")]
+const MAX_ARROW_HEAD: f32 = 8.0;
+
+/// Adds an arrow from `start` to `end` to a path
+#[cfg(feature = "app")]
+fn add_arrow(builder: &mut PathBuilder, start: Vec2, end: Vec2) {
+    builder.move_to(start);
+    builder.line_to(end);
+    let shaft = end - start;
+    let length = shaft.length();
+    if length > f32::EPSILON {
+        let head = (length * ARROW_HEAD).min(MAX_ARROW_HEAD);
+        let back = -shaft / length * head;
+        // Two barbs at 30 degrees either side of the shaft
+        for angle in [30f32, -30.0] {
+            builder.move_to(end);
+            builder.line_to(end + Vec2::from_angle(angle.to_radians()).rotate(back));
+        }
+    }
+}
+
+/// Builds the shape of an arrow, so arrows can be moved by replacing their `Path`
+///
+/// ### Arguments
+/// - `start` The tail of the arrow
+/// - `end` The tip of the arrow
+#[cfg(feature = "app")]
+pub fn arrow_path(start: Vec2, end: Vec2) -> Path {
+    return arrows_path(&[(start, end)]);
+}
+
+/// Builds a single shape out of many arrows, each given as its tail and tip
+#[cfg(feature = "app")]
+pub fn arrows_path(arrows: &[(Vec2, Vec2)]) -> Path {
+    let mut builder = PathBuilder::new();
+    for (start, end) in arrows {
+        add_arrow(&mut builder, *start, *end);
+    }
+    return builder.build();
 }
@@ -0,0 +1,94 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics::{self, ForceSolver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+mod common;
+use common::particle;
+
+const G: f32 = 6.7;
+
+fn random_particles(n: usize, seed: u64) -> Vec<Particle> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            let radius = rng.gen_range(1.0..5.0);
+            let density = rng.gen_range(0.1..10.0);
+            let pos = Vec2::new(rng.gen_range(-500.0..500.0), rng.gen_range(-500.0..500.0));
+            particle(pos, Vec2::ZERO, radius, density)
+        })
+        .collect()
+}
+
+/// Gets the largest and RMS errors of the approximate accelerations, relative to the RMS exact acceleration
+fn relative_errors(exact: &[Vec2], approx: &[Vec2]) -> (f32, f32) {
+    let rms =
+        |v: &[Vec2]| (v.iter().map(|a| a.length_squared()).sum::<f32>() / v.len() as f32).sqrt();
+    let scale = rms(exact);
+    let errors: Vec<Vec2> = exact.iter().zip(approx).map(|(e, a)| *e - *a).collect();
+    let max = errors.iter().map(|e| e.length()).fold(0.0, f32::max);
+    (max / scale, rms(&errors) / scale)
+}
+
+#[test]
+fn zero_opening_angle_matches_direct_summation() {
+    let particles = random_particles(300, 1);
+    let exact = physics::accelerations(&particles, G, 0.0, ForceSolver::Direct, 0.0);
+    let approx = physics::accelerations(&particles, G, 0.0, ForceSolver::BarnesHut, 0.0);
+    let (max, _) = relative_errors(&exact, &approx);
+    assert!(max < 1e-4, "max relative error {}", max);
+}
+
+#[test]
+fn default_opening_angle_is_accurate() {
+    let particles = random_particles(1000, 2);
+    let exact = physics::accelerations(&particles, G, 0.0, ForceSolver::Direct, 0.0);
+    let approx = physics::accelerations(&particles, G, 0.0, ForceSolver::BarnesHut, 0.5);
+    let (max, rms) = relative_errors(&exact, &approx);
+    assert!(rms < 0.01, "rms relative error {}", rms);
+    assert!(max < 0.05, "max relative error {}", max);
+}
+
+#[test]
+fn error_grows_with_opening_angle() {
+    let particles = random_particles(500, 3);
+    let exact = physics::accelerations(&particles, G, 0.0, ForceSolver::Direct, 0.0);
+    let mut prev = 0.0;
+    for theta in [0.25, 0.5, 1.0] {
+        let approx = physics::accelerations(&particles, G, 0.0, ForceSolver::BarnesHut, theta);
+        let (_, rms) = relative_errors(&exact, &approx);
+        assert!(
+            rms >= prev,
+            "theta = {} gave rms error {} < {}",
+            theta,
+            rms,
+            prev
+        );
+        prev = rms;
+    }
+}
+
+#[test]
+fn collision_pairs_match_checking_every_pair() {
+    let mut rng = StdRng::seed_from_u64(4);
+    // Crowded enough that many particles overlap, with one much larger than the rest
+    let mut particles: Vec<Particle> = (0..400)
+        .map(|_| {
+            let pos = Vec2::new(rng.gen_range(-200.0..200.0), rng.gen_range(-200.0..200.0));
+            particle(pos, Vec2::ZERO, rng.gen_range(1.0..6.0), 1.0)
+        })
+        .collect();
+    particles.push(particle(Vec2::new(-180.0, 150.0), Vec2::ZERO, 30.0, 1.0));
+
+    let mut expected = Vec::new();
+    for i in 0..particles.len() {
+        for j in i + 1..particles.len() {
+            if physics::is_intersecting(&particles[i], &particles[j]) {
+                expected.push((i, j));
+            }
+        }
+    }
+    assert!(expected.len() > 50);
+    assert_eq!(physics::collision_pairs(&particles), expected);
+}
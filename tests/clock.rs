@@ -0,0 +1,150 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics;
+use n_body::resources::clock::SimulationClock;
+use n_body::simulation::{Settings, Simulation};
+
+mod common;
+use common::particle;
+
+fn particles() -> Vec<Particle> {
+    let sun = particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1e4);
+    let pos = Vec2::new(200.0, 0.0);
+    let vel = physics::orbital_velocity(pos, sun.position(), sun.mass(), 6.7);
+    vec![sun, particle(pos, vel, 5.0, 1.0)]
+}
+
+/// Runs the simulation for a number of physics steps, with frames of the given length
+///
+/// Each step runs `substeps` simulation steps, the same as the app's physics schedule
+fn run(clock: &mut SimulationClock, frame_dt: f32, total_steps: u64) -> Simulation {
+    let mut sim = Simulation::new(particles(), Settings::new());
+    while clock.steps() < total_steps {
+        let steps = clock.advance(frame_dt);
+        for _ in 0..steps * clock.substeps.max(1) {
+            sim.step(clock.substep_dt());
+        }
+    }
+    sim
+}
+
+#[test]
+fn accumulates_partial_frames() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    assert_eq!(clock.advance(0.004), 0);
+    assert_eq!(clock.advance(0.004), 0);
+    assert_eq!(clock.advance(0.004), 1);
+    assert_eq!(clock.steps(), 1);
+}
+
+#[test]
+fn caps_steps_per_frame() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    clock.max_steps_per_frame = 4;
+    // A long frame (e.g. after the window was dragged) is worth 1000 steps
+    for _ in 0..3 {
+        assert_eq!(clock.advance(10.0), 4);
+    }
+    assert_eq!(clock.steps(), 12);
+    // The backlog is dropped instead of being carried over, less than a step is left over
+    assert!(clock.dropped_fraction() > 0.99);
+    assert_eq!(clock.advance(0.0), 0);
+    assert_eq!(clock.advance(0.01), 1);
+}
+
+#[test]
+fn reports_no_dropped_time_within_the_cap() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    clock.max_steps_per_frame = 4;
+    clock.time_scale = 2.0;
+    assert_eq!(clock.advance(0.015), 3);
+    assert_eq!(clock.dropped_fraction(), 0.0);
+    // 8 steps' worth at 2x, only 4 are run
+    clock.advance(0.04);
+    assert!((clock.dropped_fraction() - 0.5).abs() < 0.1);
+}
+
+#[test]
+fn results_do_not_depend_on_frame_rate() {
+    let steps = 600;
+    let mut slow = SimulationClock::new();
+    slow.substeps = 4;
+    slow.max_steps_per_frame = 1000;
+    let mut fast = SimulationClock::new();
+    fast.substeps = 4;
+
+    // Both clocks may overshoot by a frame, so only compare the common steps
+    let a = run(&mut slow, 1.0 / 24.0, steps);
+    let b = run(&mut fast, 1.0 / 240.0, slow.steps());
+    assert_eq!(slow.steps(), fast.steps());
+    assert_eq!(a.steps(), slow.steps() * 4);
+    assert_eq!(a.steps(), b.steps());
+    assert!((a.time() - b.time()).abs() < 1e-9);
+    for (a, b) in a.particles.iter().zip(&b.particles) {
+        assert_eq!(a.position(), b.position());
+        assert_eq!(a.velocity(), b.velocity());
+    }
+}
+
+#[test]
+fn paused_clock_only_runs_requested_steps() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    clock.toggle_pause();
+    assert_eq!(clock.advance(1.0), 0);
+    assert_eq!(clock.last_advance(), 0);
+
+    clock.step_once();
+    clock.step_once();
+    assert_eq!(clock.advance(0.0), 2);
+    assert_eq!(clock.advance(1.0), 0);
+    assert_eq!(clock.steps(), 2);
+    assert!((clock.elapsed() - 0.02).abs() < 1e-9);
+
+    clock.toggle_pause();
+    assert!(!clock.paused);
+    assert_eq!(clock.advance(0.015), 1);
+}
+
+#[test]
+fn step_once_pauses_a_running_clock() {
+    let mut clock = SimulationClock::new();
+    clock.step_once();
+    assert!(clock.paused);
+    assert_eq!(clock.advance(1.0), 1);
+}
+
+#[test]
+fn time_scale_slows_down_and_speeds_up() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    clock.max_steps_per_frame = 100;
+    clock.time_scale = 0.25;
+    let steps: usize = (0..10).map(|_| clock.advance(0.01)).sum();
+    assert_eq!(steps, 2);
+
+    clock.reset();
+    clock.time_scale = 4.0;
+    assert_eq!(clock.advance(0.1), 40);
+}
+
+#[test]
+fn reports_the_step_in_progress() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.01;
+    assert_eq!(clock.advance(0.035), 3);
+    let mut steps = Vec::new();
+    for _ in 0..3 {
+        clock.start_step();
+        steps.push(clock.current_step());
+    }
+    assert_eq!(steps.iter().map(|s| s.0).collect::<Vec<_>>(), [1, 2, 3]);
+    assert!((steps[0].1 - 0.01).abs() < 1e-9);
+    // Once every step has run it is the same as the totals
+    assert_eq!(clock.current_step(), (clock.steps(), clock.elapsed()));
+    clock.start_step();
+    assert_eq!(clock.current_step().0, 3);
+}
@@ -0,0 +1,125 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics;
+
+mod common;
+use common::particle;
+
+fn momentum(particles: &[&Particle]) -> Vec2 {
+    particles.iter().map(|p| p.velocity() * p.mass()).sum()
+}
+
+#[test]
+fn merge_conserves_mass_and_momentum() {
+    let mut a = particle(Vec2::ZERO, Vec2::new(3.0, -1.0), 5.0, 2.0);
+    let b = particle(Vec2::new(4.0, 0.0), Vec2::new(-2.0, 4.0), 3.0, 0.5);
+    let mass = a.mass() + b.mass();
+    let p = momentum(&[&a, &b]);
+    let center = (a.position() * a.mass() + b.position() * b.mass()) / mass;
+
+    physics::merge(&mut a, &b);
+
+    assert!((a.mass() - mass).abs() / mass < 1e-5);
+    assert!((a.velocity() * a.mass() - p).length() / p.length() < 1e-5);
+    assert!((a.position() - center).length() < 1e-4);
+}
+
+#[test]
+fn merge_keeps_density_and_combines_volume() {
+    let mut a = particle(Vec2::ZERO, Vec2::ZERO, 4.0, 1.0);
+    let b = particle(Vec2::X, Vec2::ZERO, 3.0, 1.0);
+    physics::merge(&mut a, &b);
+    // Equal densities, so the volumes add: r³ = 4³ + 3³
+    let expected = (4.0f32.powi(3) + 3.0f32.powi(3)).cbrt();
+    assert!((a.radius() - expected).abs() < 1e-4);
+}
+
+fn kinetic_energy(particles: &[&Particle]) -> f32 {
+    particles
+        .iter()
+        .map(|p| 0.5 * p.mass() * p.velocity().length_squared())
+        .sum()
+}
+
+/// Collides two particles head on, returning them after the collision
+fn head_on(r: f32) -> (Particle, Particle) {
+    let mut a = particle(Vec2::ZERO, Vec2::new(4.0, 0.0), 5.0, 2.0);
+    let mut b = particle(Vec2::new(8.0, 0.0), Vec2::new(-1.0, 0.0), 4.0, 1.0);
+    assert!(physics::is_intersecting(&a, &b));
+    physics::resolve_intersection(&mut a, &mut b);
+    physics::resolve_collision(&mut a, &mut b, r);
+    (a, b)
+}
+
+#[test]
+fn collision_conserves_momentum() {
+    let a = particle(Vec2::ZERO, Vec2::new(4.0, 0.0), 5.0, 2.0);
+    let b = particle(Vec2::new(8.0, 0.0), Vec2::new(-1.0, 0.0), 4.0, 1.0);
+    let before = momentum(&[&a, &b]);
+    for r in [0.0, 0.25, 0.5, 0.8, 1.0] {
+        let (a, b) = head_on(r);
+        let after = momentum(&[&a, &b]);
+        assert!(
+            (after - before).length() / before.length() < 1e-5,
+            "r = {}: {} != {}",
+            r,
+            after,
+            before
+        );
+    }
+}
+
+#[test]
+fn collision_scales_relative_kinetic_energy_by_restitution_squared() {
+    let a = particle(Vec2::ZERO, Vec2::new(4.0, 0.0), 5.0, 2.0);
+    let b = particle(Vec2::new(8.0, 0.0), Vec2::new(-1.0, 0.0), 4.0, 1.0);
+    let total_mass = a.mass() + b.mass();
+    // Kinetic energy of the centre of mass can't change
+    let ke_com = 0.5 * momentum(&[&a, &b]).length_squared() / total_mass;
+    let ke_before = kinetic_energy(&[&a, &b]);
+    for r in [0.0, 0.25, 0.5, 0.8, 1.0] {
+        let (a, b) = head_on(r);
+        let ratio = (kinetic_energy(&[&a, &b]) - ke_com) / (ke_before - ke_com);
+        assert!((ratio - r * r).abs() < 1e-4, "r = {}: ratio {}", r, ratio);
+    }
+}
+
+#[test]
+fn perfectly_inelastic_collision_matches_velocities() {
+    let (a, b) = head_on(0.0);
+    assert!((a.velocity() - b.velocity()).length() < 1e-5);
+}
+
+#[test]
+fn collision_is_symmetric() {
+    let a = particle(Vec2::ZERO, Vec2::new(4.0, 1.0), 5.0, 2.0);
+    let b = particle(Vec2::new(6.0, 6.0), Vec2::new(-1.0, -2.0), 4.0, 1.0);
+    let (mut a1, mut b1) = (a.clone(), b.clone());
+    physics::resolve_collision(&mut a1, &mut b1, 0.7);
+    let (mut a2, mut b2) = (a.clone(), b.clone());
+    physics::resolve_collision(&mut b2, &mut a2, 0.7);
+    assert!((a1.velocity() - a2.velocity()).length() < 1e-5);
+    assert!((b1.velocity() - b2.velocity()).length() < 1e-5);
+}
+
+#[test]
+fn separating_particles_are_not_resolved() {
+    let mut a = particle(Vec2::ZERO, Vec2::new(-1.0, 0.0), 5.0, 1.0);
+    let mut b = particle(Vec2::new(8.0, 0.0), Vec2::new(1.0, 0.0), 5.0, 1.0);
+    physics::resolve_collision(&mut a, &mut b, 1.0);
+    assert_eq!(a.velocity(), Vec2::new(-1.0, 0.0));
+    assert_eq!(b.velocity(), Vec2::new(1.0, 0.0));
+}
+
+#[test]
+fn intersection_is_removed_without_moving_center_of_mass() {
+    let mut a = particle(Vec2::ZERO, Vec2::ZERO, 5.0, 2.0);
+    let mut b = particle(Vec2::new(6.0, 3.0), Vec2::ZERO, 4.0, 1.0);
+    let mass = a.mass() + b.mass();
+    let center = (a.position() * a.mass() + b.position() * b.mass()) / mass;
+    physics::resolve_intersection(&mut a, &mut b);
+    let dist = (a.position() - b.position()).length();
+    assert!((dist - (a.radius() + b.radius())).abs() < 1e-4);
+    let after = (a.position() * a.mass() + b.position() * b.mass()) / mass;
+    assert!((after - center).length() < 1e-4);
+}
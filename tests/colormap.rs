@@ -0,0 +1,76 @@
+use glam::Vec2;
+use n_body::colormap::{self, ColorMode, ColorScale, Colormap};
+use n_body::particle::Particle;
+
+mod common;
+use common::particle;
+
+fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-3)
+}
+
+#[test]
+fn colormaps_span_their_stops() {
+    // Viridis runs from dark purple to yellow
+    assert!(close(
+        Colormap::Viridis.sample(0.0),
+        [68.0 / 255.0, 1.0 / 255.0, 84.0 / 255.0]
+    ));
+    assert!(close(
+        Colormap::Viridis.sample(1.0),
+        [253.0 / 255.0, 231.0 / 255.0, 37.0 / 255.0]
+    ));
+    assert!(close(Colormap::Greys.sample(0.5), [0.5, 0.5, 0.5]));
+    // Out of range values are clamped
+    for colormap in Colormap::ALL {
+        assert_eq!(colormap.sample(-1.0), colormap.sample(0.0));
+        assert_eq!(colormap.sample(2.0), colormap.sample(1.0));
+        assert_eq!(colormap.sample(f32::NAN), colormap.sample(0.0));
+    }
+}
+
+#[test]
+fn scale_spans_particles() {
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::new(1.0, 0.0), 1.0, 1.0),
+        particle(Vec2::ZERO, Vec2::new(0.0, 3.0), 1.0, 1.0),
+        particle(Vec2::ZERO, Vec2::new(-5.0, 0.0), 1.0, 1.0),
+    ];
+    let scale = ColorScale::new(ColorMode::Speed, &particles).unwrap();
+    assert_eq!((scale.min, scale.max), (1.0, 5.0));
+    assert_eq!(scale.position(&particles[0]), 0.0);
+    assert_eq!(scale.position(&particles[1]), 0.5);
+    assert_eq!(scale.position(&particles[2]), 1.0);
+
+    // Not continuous, or nothing to scale
+    assert!(ColorScale::new(ColorMode::Assigned, &particles).is_none());
+    assert!(ColorScale::new(ColorMode::Id, &particles).is_none());
+    assert!(ColorScale::new(ColorMode::Speed, &[]).is_none());
+}
+
+#[test]
+fn mass_scale_is_logarithmic() {
+    let particles: Vec<Particle> = [1.0, 10.0, 100.0]
+        .iter()
+        .map(|density| particle(Vec2::ZERO, Vec2::ZERO, 1.0, *density))
+        .collect();
+    let scale = ColorScale::new(ColorMode::Mass, &particles).unwrap();
+    assert!((scale.position(&particles[1]) - 0.5).abs() < 1e-4);
+
+    // Identical particles don't divide by zero
+    let same = vec![particle(Vec2::ZERO, Vec2::ZERO, 1.0, 1.0); 2];
+    let scale = ColorScale::new(ColorMode::Mass, &same).unwrap();
+    assert_eq!(scale.position(&same[0]), 0.0);
+}
+
+#[test]
+fn hues_are_stable_and_spread() {
+    assert_eq!(colormap::hue(7), colormap::hue(7));
+    for id in 0..100 {
+        let hue = colormap::hue(id);
+        assert!((0.0..360.0).contains(&hue));
+        // Consecutive IDs are never given similar hues
+        let diff = (hue - colormap::hue(id + 1)).abs();
+        assert!(diff.min(360.0 - diff) > 60.0);
+    }
+}
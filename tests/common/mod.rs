@@ -0,0 +1,25 @@
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use glam::Vec2;
+use n_body::particle::Particle;
+
+/// Creates a particle at a position, moving with a velocity
+pub fn particle(pos: Vec2, vel: Vec2, radius: f32, density: f32) -> Particle {
+    let mut p = Particle::default();
+    p.set_radius(radius).unwrap();
+    p.set_density(density).unwrap();
+    p.set_pos(pos);
+    p.set_vel(vel);
+    p
+}
+
+/// Creates a particle with a given mass, its density is chosen to match its radius
+pub fn particle_with_mass(pos: Vec2, vel: Vec2, radius: f32, mass: f32) -> Particle {
+    let mut p = Particle::default();
+    p.set_radius(radius).unwrap();
+    p.set_mass_with_density(mass);
+    p.set_pos(pos);
+    p.set_vel(vel);
+    p
+}
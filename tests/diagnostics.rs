@@ -0,0 +1,80 @@
+use glam::{DVec2, Vec2};
+use n_body::particle::Particle;
+use n_body::physics::diagnostics::{self, ConservedQuantities};
+
+mod common;
+use common::particle_with_mass;
+
+const TOLERANCE: f64 = 1e-5;
+
+/// A mass of 2 moving up at the origin, and a mass of 1 three units to its right
+fn two_bodies() -> Vec<Particle> {
+    vec![
+        particle_with_mass(Vec2::ZERO, Vec2::new(0.0, 1.0), 1.0, 2.0),
+        particle_with_mass(Vec2::new(3.0, 0.0), Vec2::new(1.0, -1.0), 1.0, 1.0),
+    ]
+}
+
+fn assert_close(actual: f64, expected: f64, what: &str) {
+    assert!(
+        (actual - expected).abs() < TOLERANCE,
+        "{}: expected {}, got {}",
+        what,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn measures_a_two_body_system() {
+    let q = ConservedQuantities::measure(&two_bodies(), 1.0, 0.0);
+    // ½·2·1² + ½·1·(1² + 1²)
+    assert_close(q.kinetic_energy, 2.0, "kinetic energy");
+    // -G·2·1 / 3
+    assert_close(q.potential_energy, -2.0 / 3.0, "potential energy");
+    assert_close(q.total_energy(), 4.0 / 3.0, "total energy");
+    // 2·(0, 1) + 1·(1, -1)
+    assert_close(q.momentum.x, 1.0, "momentum x");
+    assert_close(q.momentum.y, 1.0, "momentum y");
+    // Only the second body is away from the origin: 1·(3·-1 - 0·1)
+    assert_close(q.angular_momentum, -3.0, "angular momentum");
+    // (2·0 + 1·3) / 3
+    assert!((q.center_of_mass - DVec2::new(1.0, 0.0)).length() < TOLERANCE);
+    assert_close(q.total_mass, 3.0, "total mass");
+}
+
+#[test]
+fn softening_lowers_the_potential_energy() {
+    let particles = two_bodies();
+    // -G·2·1 / √(3² + 4²)
+    let q = ConservedQuantities::measure(&particles, 1.0, 4.0);
+    assert_close(q.potential_energy, -0.4, "softened potential energy");
+    assert_close(
+        diagnostics::potential_energy(&particles[0], &particles[1], 1.0, 4.0) as f64,
+        -0.4,
+        "pair potential energy",
+    );
+}
+
+#[test]
+fn unchanged_system_has_no_drift() {
+    let q = ConservedQuantities::measure(&two_bodies(), 1.0, 0.0);
+    assert_eq!(q.energy_drift(&q), 0.0);
+    assert_eq!(q.momentum_drift(&q), 0.0);
+    assert_eq!(q.angular_momentum_drift(&q), 0.0);
+}
+
+#[test]
+fn drift_from_a_zero_baseline_is_absolute() {
+    // Nothing to measure, so every total is zero and there is nothing to scale by
+    let empty = ConservedQuantities::measure(&[], 1.0, 0.0);
+    assert_eq!(empty.total_energy(), 0.0);
+    let q = ConservedQuantities::measure(&two_bodies(), 1.0, 0.0);
+    assert_close(q.energy_drift(&empty), 4.0 / 3.0, "energy drift");
+    assert_close(q.momentum_drift(&empty), 2f64.sqrt(), "momentum drift");
+    assert_close(
+        q.angular_momentum_drift(&empty),
+        3.0,
+        "angular momentum drift",
+    );
+}
@@ -0,0 +1,167 @@
+use glam::Vec2;
+use n_body::export::{ExportFormat, TrajectoryWriter, COLUMNS};
+use n_body::particle::Particle;
+use n_body::physics::CollisionMode;
+use n_body::simulation::{Settings, Simulation};
+use std::io::Read;
+use std::path::PathBuf;
+
+mod common;
+use common::particle;
+
+fn three_particles() -> Simulation {
+    Simulation::new(
+        vec![
+            particle(Vec2::new(-100.0, 0.0), Vec2::new(0.0, 1.0), 2.0, 1.0),
+            particle(Vec2::new(0.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+            particle(Vec2::new(100.0, 0.0), Vec2::new(0.0, -1.0), 3.0, 1.0),
+        ],
+        Settings::new(),
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("n_body_export_test_{}", name))
+}
+
+#[test]
+fn ids_survive_merges() {
+    let mut settings = Settings::new();
+    settings.collision_mode = CollisionMode::Merge;
+    let mut sim = Simulation::new(
+        vec![
+            particle(Vec2::new(-100.0, 0.0), Vec2::ZERO, 2.0, 1.0),
+            particle(Vec2::new(0.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+            particle(Vec2::new(3.0, 0.0), Vec2::ZERO, 10.0, 1.0),
+        ],
+        settings,
+    );
+    assert_eq!(sim.ids(), &[0, 1, 2]);
+    sim.step(1.0 / 120.0);
+    assert_eq!(sim.particles.len(), 2);
+    // The lighter particle is absorbed, the heavier keeps its ID
+    assert_eq!(sim.ids(), &[0, 2]);
+    assert_eq!(sim.add(Particle::default()), 3);
+    assert_eq!(sim.ids(), &[0, 2, 3]);
+}
+
+#[test]
+fn format_is_chosen_by_extension() {
+    let format = |name: &str| ExportFormat::from_path(&PathBuf::from(name)).ok();
+    assert_eq!(format("a.csv"), Some(ExportFormat::Csv));
+    assert_eq!(format("a.npy"), Some(ExportFormat::Npy));
+    assert_eq!(format("a.npz"), Some(ExportFormat::Npz));
+    assert_eq!(format("a.txt"), None);
+}
+
+#[test]
+fn writes_csv_rows() {
+    let sim = three_particles();
+    let path = temp_path("rows.csv");
+    let mut writer = TrajectoryWriter::create(&path).unwrap();
+    writer.write(0.5, sim.ids(), &sim.particles).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(writer.rows(), 3);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "id,t,x,y,vx,vy,mass,radius");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].starts_with("2,0.5,100,0,0,-1,"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Splits a `.npy` file into its header dictionary and data
+fn parse_npy(bytes: &[u8]) -> (String, &[u8]) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!((10 + len) % 64, 0);
+    let header = String::from_utf8(bytes[10..10 + len].to_vec()).unwrap();
+    assert!(header.ends_with('\n'));
+    (header, &bytes[10 + len..])
+}
+
+#[test]
+fn streams_npy_table() {
+    let mut sim = three_particles();
+    let path = temp_path("table.npy");
+    let mut writer = TrajectoryWriter::create(&path).unwrap();
+    writer.write(sim.time(), sim.ids(), &sim.particles).unwrap();
+    sim.step(0.25);
+    writer.write(sim.time(), sim.ids(), &sim.particles).unwrap();
+    writer.finish().unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    let (header, data) = parse_npy(&bytes);
+    assert!(header.contains("'descr': '<f8'"));
+    assert!(header.contains("'shape': (6, 8)"));
+    assert_eq!(data.len(), 6 * COLUMNS.len() * 8);
+    let value = |row: usize, column: usize| {
+        let start = (row * COLUMNS.len() + column) * 8;
+        f64::from_le_bytes(data[start..start + 8].try_into().unwrap())
+    };
+    assert_eq!(value(4, 0), 1.0);
+    assert_eq!(value(4, 1), 0.25);
+    assert_eq!(value(5, 2), sim.particles[2].position().x as f64);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn writes_npz_columns() {
+    let sim = three_particles();
+    let path = temp_path("columns.npz");
+    let mut writer = TrajectoryWriter::create(&path).unwrap();
+    writer.write(1.0, sim.ids(), &sim.particles).unwrap();
+    drop(writer);
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    for column in COLUMNS {
+        let mut bytes = Vec::new();
+        archive
+            .by_name(&format!("{}.npy", column))
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        let (header, data) = parse_npy(&bytes);
+        assert!(header.contains("'shape': (3,)"));
+        assert_eq!(data.len(), 3 * 8);
+        if column == "id" {
+            assert!(header.contains("'descr': '<u8'"));
+            assert_eq!(u64::from_le_bytes(data[16..24].try_into().unwrap()), 2);
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn npz_columns_are_streamed_and_cleaned_up() {
+    let mut sim = three_particles();
+    let path = temp_path("streamed.npz");
+    let column = |name: &str| temp_path(&format!("streamed.npz.{}.tmp", name));
+    let mut writer = TrajectoryWriter::create(&path).unwrap();
+    let mut time = 0.0;
+    for _ in 0..1000 {
+        time = sim.time();
+        writer.write(time, sim.ids(), &sim.particles).unwrap();
+        sim.step(0.01);
+    }
+    // Rows go to disk as they are written, rather than being kept until the end
+    assert!(std::fs::metadata(column("x")).unwrap().len() > 0);
+    writer.finish().unwrap();
+    for name in COLUMNS {
+        assert!(!column(name).exists(), "{}", name);
+    }
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut bytes = Vec::new();
+    archive
+        .by_name("t.npy")
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    let (header, data) = parse_npy(&bytes);
+    assert!(header.contains("'shape': (3000,)"));
+    let last = f64::from_le_bytes(data[data.len() - 8..].try_into().unwrap());
+    assert_eq!(last, time);
+    std::fs::remove_file(&path).unwrap();
+}
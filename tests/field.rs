@@ -0,0 +1,83 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics::field::{FieldGrid, FieldQuantity};
+use n_body::physics::{self, ForceSolver};
+
+mod common;
+use common::particle;
+
+const G: f32 = 6.7;
+
+fn sample(particles: &[Particle], n: usize) -> FieldGrid {
+    FieldGrid::sample(
+        particles,
+        Vec2::splat(-200.0),
+        Vec2::splat(200.0),
+        n,
+        n,
+        G,
+        1.0,
+    )
+}
+
+#[test]
+fn samples_use_the_force_law() {
+    let particles = vec![
+        particle(Vec2::new(-50.0, 10.0), Vec2::ZERO, 5.0, 10.0),
+        particle(Vec2::new(70.0, -30.0), Vec2::ZERO, 5.0, 20.0),
+    ];
+    let grid = sample(&particles, 8);
+    assert_eq!(grid.potential.len(), 64);
+    // Cell centres
+    assert_eq!(grid.position(0, 0), Vec2::splat(-175.0));
+    assert_eq!(grid.position(7, 7), Vec2::splat(175.0));
+
+    for y in 0..8 {
+        for x in 0..8 {
+            let pos = grid.position(x, y);
+            // The acceleration a massless probe would feel in the simulation
+            let mut probe = particle(pos, Vec2::ZERO, 5.0, 1e-9);
+            probe.set_radius(1e-3).unwrap();
+            let mut all = particles.clone();
+            all.push(probe);
+            let acc = physics::accelerations(&all, G, 1.0, ForceSolver::Direct, 0.0)[2];
+            let field = grid.field[y * 8 + x];
+            assert!((field - acc).length() <= 1e-4 * acc.length());
+
+            let potential: f32 = particles
+                .iter()
+                .map(|p| physics::gravitational_potential(pos, p.position(), p.mass(), G, 1.0))
+                .sum();
+            assert!((grid.potential[y * 8 + x] - potential).abs() <= 1e-4 * potential.abs());
+        }
+    }
+}
+
+#[test]
+fn contours_around_a_point_mass_are_circles() {
+    let particles = vec![particle(Vec2::ZERO, Vec2::ZERO, 5.0, 10.0)];
+    let grid = sample(&particles, 64);
+    let values = grid.magnitudes(FieldQuantity::Potential);
+    let segments = grid.contours(&values, 5);
+    assert!(!segments.is_empty());
+    for (a, b) in segments {
+        // Both ends lie on the same equipotential, so are the same distance from the mass
+        let (ra, rb) = (a.length(), b.length());
+        assert!((ra - rb).abs() < 0.1 * ra.max(rb), "{} {}", ra, rb);
+    }
+}
+
+#[test]
+fn field_vanishes_at_the_saddle_between_equal_masses() {
+    let particles = vec![
+        particle(Vec2::new(-100.0, 0.0), Vec2::ZERO, 5.0, 10.0),
+        particle(Vec2::new(100.0, 0.0), Vec2::ZERO, 5.0, 10.0),
+    ];
+    let grid = sample(&particles, 41);
+    let strength = grid.magnitudes(FieldQuantity::Strength);
+    let weakest = (0..strength.len())
+        .min_by(|a, b| strength[*a].total_cmp(&strength[*b]))
+        .unwrap();
+    let pos = grid.position(weakest % 41, weakest / 41);
+    assert!(pos.length() < 10.0, "weakest at {:?}", pos);
+}
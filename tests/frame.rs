@@ -0,0 +1,105 @@
+use glam::Vec2;
+use n_body::frame::{self, FramePoint};
+use n_body::particle::path::Path;
+use n_body::physics::{self, integrator::IntegratorKind};
+use n_body::simulation::{Settings, Simulation};
+
+mod common;
+use common::particle;
+
+/// A planet on a circular orbit of radius 100 around a sun drifting to the right, with the
+/// trails of both recorded every step
+fn drifting_orbit() -> (Simulation, Path, Path) {
+    let mut settings = Settings::new();
+    settings.constants.softening.value = 0.0;
+    settings.integrator = IntegratorKind::Yoshida4;
+    let drift = Vec2::new(30.0, 0.0);
+    let sun = particle(Vec2::ZERO, drift, 5.0, 1e4);
+    let pos = Vec2::new(0.0, 100.0);
+    let vel =
+        physics::orbital_velocity(pos, sun.position(), sun.mass(), settings.constants.g.value);
+    let planet = particle(pos, vel + drift, 0.1, 1e-3);
+
+    let mut sim = Simulation::new(vec![sun, planet], settings);
+    let mut sun_path = Path::new(500);
+    let mut planet_path = Path::new(500);
+    for _ in 0..500 {
+        sim.run(1.0 / 120.0, 1);
+        sun_path.add_point(sim.particles[0].position());
+        planet_path.add_point(sim.particles[1].position());
+    }
+    (sim, sun_path, planet_path)
+}
+
+fn translating(path: &Path) -> Vec<FramePoint> {
+    path.points
+        .iter()
+        .map(|p| FramePoint::translating(*p))
+        .collect()
+}
+
+#[test]
+fn trail_relative_to_a_body() {
+    let (sim, sun_path, planet_path) = drifting_orbit();
+    let sun = sim.particles[0].position();
+
+    // In the inertial frame the trail is stretched along the drift
+    let inertial_spread = planet_path
+        .points
+        .iter()
+        .map(|p| p.x)
+        .fold(f32::MIN, f32::max)
+        - planet_path
+            .points
+            .iter()
+            .map(|p| p.x)
+            .fold(f32::MAX, f32::min);
+    assert!(inertial_spread > 200.0);
+
+    // Relative to the sun it is a circle around where the sun is now
+    let projected = frame::project(&planet_path, &translating(&sun_path));
+    assert_eq!(projected.len(), 500);
+    for p in &projected {
+        assert!((p.distance(sun) - 100.0).abs() < 1.0);
+    }
+    assert!(
+        projected
+            .last()
+            .unwrap()
+            .distance(sim.particles[1].position())
+            < 1e-3
+    );
+}
+
+#[test]
+fn trail_in_corotating_frame() {
+    let (sim, sun_path, planet_path) = drifting_orbit();
+    let (sun, planet) = (&sim.particles[0], &sim.particles[1]);
+    let frames: Vec<FramePoint> = sun_path
+        .points
+        .iter()
+        .zip(&planet_path.points)
+        .map(|(s, p)| FramePoint::corotating(*s, sun.mass(), *p, planet.mass()))
+        .collect();
+
+    // The planet doesn't move in a frame rotating with the orbit
+    let projected = frame::project(&planet_path, &frames);
+    for p in &projected {
+        assert!(p.distance(planet.position()) < 1.0);
+    }
+}
+
+#[test]
+fn projection_drops_points_without_history() {
+    let mut trail = Path::new(10);
+    for x in 0..10 {
+        trail.add_point(Vec2::new(x as f32, 0.0));
+    }
+    // The frame only started recording 4 samples ago, and hasn't moved
+    let frames = vec![FramePoint::translating(Vec2::ZERO); 4];
+    let projected = frame::project(&trail, &frames);
+    let xs: Vec<f32> = projected.iter().map(|p| p.x).collect();
+    assert_eq!(xs, vec![6.0, 7.0, 8.0, 9.0]);
+
+    assert!(frame::project(&trail, &[]).is_empty());
+}
@@ -0,0 +1,141 @@
+use glam::Vec2;
+use n_body::generators::{self, GeneratorKind, GeneratorParams};
+use n_body::particle::Particle;
+use n_body::physics::diagnostics::ConservedQuantities;
+use n_body::physics::orbit::OrbitalElements;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const G: f32 = 6.7;
+
+fn positions(particles: &[Particle]) -> Vec<Vec2> {
+    particles.iter().map(|p| p.position()).collect()
+}
+
+#[test]
+fn generators_are_seeded() {
+    for kind in GeneratorKind::ALL {
+        let params = GeneratorParams {
+            kind,
+            count: 50,
+            ..Default::default()
+        };
+        let a = params.generate(G, 0.0);
+        let b = params.generate(G, 0.0);
+        assert!(!a.is_empty(), "{:?}", kind);
+        assert_eq!(positions(&a), positions(&b), "{:?}", kind);
+        for p in &a {
+            assert!(p.position().is_finite() && p.velocity().is_finite());
+            assert!(p.mass() > 0.0);
+        }
+    }
+    let seeded = |seed| GeneratorParams {
+        seed,
+        ..Default::default()
+    };
+    assert_ne!(
+        positions(&seeded(1).generate(G, 0.0)),
+        positions(&seeded(2).generate(G, 0.0))
+    );
+}
+
+#[test]
+fn plummer_sphere_is_in_virial_equilibrium() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let particles = generators::plummer_sphere(300, 80.0, 1e6, G, 0.0, &mut rng);
+    assert_eq!(particles.len(), 300);
+    let q = ConservedQuantities::measure(&particles, G, 0.0);
+    let virial = 2.0 * q.kinetic_energy / -q.potential_energy;
+    assert!((virial - 1.0).abs() < 1e-3, "2K/|W| = {}", virial);
+    // The centre of mass is at rest
+    assert!(q.momentum.length() / q.total_mass < 1e-3);
+    assert!(q.center_of_mass.length() < 1e-2);
+}
+
+#[test]
+fn softened_plummer_sphere_is_in_virial_equilibrium() {
+    let softening = 20.0;
+    let mut rng = StdRng::seed_from_u64(7);
+    let particles = generators::plummer_sphere(300, 80.0, 1e6, G, softening, &mut rng);
+    let q = ConservedQuantities::measure(&particles, G, softening);
+    let virial = generators::virial(&particles, G, softening) as f64;
+    let ratio = 2.0 * q.kinetic_energy / -virial;
+    assert!((ratio - 1.0).abs() < 1e-3, "2K/|Σ r·F| = {}", ratio);
+    // Softening weakens the binding, so the sphere is slower than an unsoftened one
+    let mut rng = StdRng::seed_from_u64(7);
+    let unsoftened = generators::plummer_sphere(300, 80.0, 1e6, G, 0.0, &mut rng);
+    let k = ConservedQuantities::measure(&unsoftened, G, 0.0).kinetic_energy;
+    assert!(q.kinetic_energy < k);
+}
+
+#[test]
+fn exponential_disk_rotates_around_the_centre() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let particles = generators::exponential_disk(200, 60.0, 2e5, 5e6, G, &mut rng);
+    assert_eq!(particles.len(), 201);
+    let q = ConservedQuantities::measure(&particles, G, 0.0);
+    assert!(q.angular_momentum > 0.0);
+    assert!(particles[1..]
+        .iter()
+        .all(|p| p.position().length() <= 5.0 * 60.0 + 1e-3));
+    let bound = particles[1..]
+        .iter()
+        .filter(|p| {
+            OrbitalElements::new(p, &particles[0], G)
+                .map(|e| e.eccentricity < 1.0)
+                .unwrap_or(false)
+        })
+        .count();
+    assert_eq!(bound, 200);
+}
+
+#[test]
+fn keplerian_ring_is_circular() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let particles = generators::keplerian_ring(100, 150.0, 200.0, 5e6, G, &mut rng);
+    assert_eq!(particles.len(), 101);
+    for p in &particles[1..] {
+        let r = p.position().length();
+        assert!((150.0..=200.0).contains(&r));
+        let elements = OrbitalElements::new(p, &particles[0], G).unwrap();
+        assert!(elements.eccentricity < 1e-3);
+        assert!(p.mass() < particles[0].mass() * 1e-6);
+    }
+}
+
+#[test]
+fn hierarchical_triple_is_bound_and_at_rest() {
+    let particles = generators::hierarchical_triple(5e6, 2.5e6, 1e6, 40.0, 200.0, G);
+    assert_eq!(particles.len(), 3);
+    let q = ConservedQuantities::measure(&particles, G, 0.0);
+    assert!(q.momentum.length() / q.total_mass < 1e-3);
+    assert!(q.center_of_mass.length() < 1e-2);
+    let inner = OrbitalElements::new(&particles[1], &particles[0], G).unwrap();
+    assert!(inner.eccentricity < 1e-3);
+    assert!((inner.semi_major_axis - 40.0).abs() < 0.1);
+
+    let binary = generators::binary(5e6, 5e6, 100.0, G);
+    assert_eq!(binary[0].position(), -binary[1].position());
+    assert_eq!(binary[0].velocity(), -binary[1].velocity());
+}
+
+#[test]
+fn grid_is_centred() {
+    let particles = generators::grid(Vec2::new(100.0, -50.0), 3, 2.0, 4.0, 1.0);
+    assert_eq!(particles.len(), 9);
+    assert_eq!(particles[0].position(), Vec2::new(90.0, -60.0));
+    assert_eq!(particles[4].position(), Vec2::new(100.0, -50.0));
+    assert_eq!(particles[8].position(), Vec2::new(110.0, -40.0));
+}
+
+#[test]
+fn grid_generator_spawns_the_requested_count() {
+    for count in [1, 10, 16, 50] {
+        let params = GeneratorParams {
+            kind: GeneratorKind::Grid,
+            count,
+            ..Default::default()
+        };
+        assert_eq!(params.generate(G, 0.0).len(), count);
+    }
+}
@@ -0,0 +1,56 @@
+use n_body::history::{History, Sample, Series};
+
+fn sample(time: f64) -> Sample {
+    Sample {
+        time,
+        total_energy: -2.0,
+        kinetic_energy: 1.0,
+        potential_energy: -3.0,
+        momentum: 0.5,
+        particles: 3,
+        step_time: 0.125,
+    }
+}
+
+#[test]
+fn drops_samples_outside_the_window() {
+    let mut history = History::new(10.0);
+    for i in 0..=30 {
+        history.push(sample(i as f64));
+    }
+    let times: Vec<f64> = history.samples().map(|s| s.time).collect();
+    assert_eq!(times.first(), Some(&20.0));
+    assert_eq!(times.last(), Some(&30.0));
+    assert_eq!(history.len(), 11);
+}
+
+#[test]
+fn restarting_clears_the_history() {
+    let mut history = History::new(10.0);
+    for i in 0..5 {
+        history.push(sample(i as f64));
+    }
+    history.push(sample(0.5));
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.series(Series::Momentum), vec![[0.5, 0.5]]);
+}
+
+#[test]
+fn csv_has_a_header_and_a_row_per_sample() {
+    let mut history = History::new(10.0);
+    history.push(sample(1.0));
+    history.push(sample(2.0));
+    let csv = history.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "time,total_energy,kinetic_energy,potential_energy,momentum,particles,step_time_ms"
+    );
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2], "2,-2,1,-3,0.5,3,125");
+
+    let path = std::env::temp_dir().join("n_body_history_test.csv");
+    history.save_csv(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,56 @@
+use glam::Vec2;
+use n_body::initial_conditions;
+use n_body::physics::{integrator::IntegratorKind, ForceSolver};
+use std::path::Path;
+
+#[test]
+fn rejects_trailing_comments() {
+    let text = "# x y vx vy radius density\n\n0 0 0 0 5 10000\n150 0 0 -12.5 5 1 # planet\n";
+    let err = initial_conditions::parse(text).err().unwrap();
+    assert!(format!("{:#}", err).contains("line 4"));
+}
+
+#[test]
+fn parses_particles_and_skips_comment_lines() {
+    let particles =
+        initial_conditions::parse("# sun\n0 0 0 0 5 10000\n\n150 0 0 -12.5 5 1\n").unwrap();
+    assert_eq!(particles.len(), 2);
+    assert_eq!(particles[1].position(), Vec2::new(150.0, 0.0));
+    assert_eq!(particles[1].velocity(), Vec2::new(0.0, -12.5));
+    assert_eq!(particles[0].density(), 10000.0);
+}
+
+#[test]
+fn rejects_invalid_particles() {
+    assert!(initial_conditions::parse("0 0 0 0 5").is_err());
+    assert!(initial_conditions::parse("0 0 0 0 -5 1").is_err());
+}
+
+#[test]
+fn loads_scenarios_and_tables() {
+    let scenario = initial_conditions::load_scenario(Path::new("scenarios/binary.json")).unwrap();
+    assert_eq!(scenario.bodies.len(), 2);
+    assert_eq!(scenario.constants.g, Some(6.7));
+    assert_eq!(scenario.constants.softening, Some(1.0));
+
+    let table = initial_conditions::load_scenario(Path::new("scenarios/solar_system.txt")).unwrap();
+    assert_eq!(table.name, "solar_system");
+    assert_eq!(table.constants.g, None);
+    let particles = table.particles().unwrap();
+    let expected = initial_conditions::load(Path::new("scenarios/solar_system.txt")).unwrap();
+    assert_eq!(particles.len(), expected.len());
+    assert_eq!(particles[1].position(), expected[1].position());
+    assert_eq!(particles[1].mass(), expected[1].mass());
+}
+
+#[test]
+fn options_parse_from_their_names() {
+    for kind in IntegratorKind::ALL {
+        assert_eq!(kind.name().parse::<IntegratorKind>().unwrap(), kind);
+    }
+    assert_eq!(
+        "barnes-hut".parse::<ForceSolver>().unwrap(),
+        ForceSolver::BarnesHut
+    );
+    assert!("leapfrog".parse::<IntegratorKind>().is_err());
+}
@@ -0,0 +1,110 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics::{self, integrator::IntegratorKind, ForceSolver};
+
+mod common;
+use common::particle_with_mass;
+
+const G: f32 = 1.0;
+const PERIODS: usize = 10;
+const STEPS_PER_PERIOD: usize = 500;
+
+/// A light satellite on an eccentric orbit around a heavy body, in the centre of mass frame
+fn kepler_orbit(eccentricity: f32) -> (Vec<Particle>, f32) {
+    let (m1, m2) = (1000.0, 1.0);
+    let a = 100.0;
+    let mu = G * (m1 + m2);
+
+    // Start at periapsis
+    let r = a * (1.0 - eccentricity);
+    let v = (mu * (1.0 + eccentricity) / r).sqrt();
+
+    let total = m1 + m2;
+    let sun = particle_with_mass(
+        Vec2::new(-r * m2 / total, 0.0),
+        Vec2::new(0.0, -v * m2 / total),
+        1.0,
+        m1,
+    );
+    let planet = particle_with_mass(
+        Vec2::new(r * m1 / total, 0.0),
+        Vec2::new(0.0, v * m1 / total),
+        0.1,
+        m2,
+    );
+
+    let period = 2.0 * std::f32::consts::PI * (a.powf(3.0) / mu).sqrt();
+    (vec![sun, planet], period)
+}
+
+fn energy(particles: &[Particle]) -> f64 {
+    let mut e = 0.0;
+    for (i, a) in particles.iter().enumerate() {
+        e += 0.5 * a.mass() as f64 * a.velocity().length_squared() as f64;
+        for b in &particles[i + 1..] {
+            let r = (a.position() - b.position()).length() as f64;
+            e -= (G * a.mass() * b.mass()) as f64 / r;
+        }
+    }
+    e
+}
+
+/// Gets the largest relative energy error over the run
+fn energy_drift(kind: IntegratorKind) -> f64 {
+    let (mut particles, period) = kepler_orbit(0.5);
+    let dt = period / STEPS_PER_PERIOD as f32;
+    let e0 = energy(&particles);
+    let accelerations =
+        |ps: &[Particle]| physics::accelerations(ps, G, 0.0, ForceSolver::Direct, 0.0);
+    let mut max_drift: f64 = 0.0;
+    for _ in 0..PERIODS * STEPS_PER_PERIOD {
+        kind.integrator().step(&mut particles, dt, &accelerations);
+        max_drift = max_drift.max(((energy(&particles) - e0) / e0).abs());
+    }
+    max_drift
+}
+
+/// Checks the largest relative energy error stays below a bound, reporting it if it doesn't
+fn assert_drift_below(kind: IntegratorKind, bound: f64) {
+    let drift = energy_drift(kind);
+    assert!(
+        drift < bound,
+        "{}: max relative energy drift {:e}",
+        kind.label(),
+        drift
+    );
+}
+
+#[test]
+fn semi_implicit_euler_energy_drift() {
+    assert_drift_below(IntegratorKind::SemiImplicitEuler, 5e-2);
+}
+
+#[test]
+fn velocity_verlet_energy_drift() {
+    assert_drift_below(IntegratorKind::VelocityVerlet, 2e-3);
+}
+
+#[test]
+fn runge_kutta_4_energy_drift() {
+    assert_drift_below(IntegratorKind::RungeKutta4, 1e-4);
+}
+
+#[test]
+fn yoshida_4_energy_drift() {
+    assert_drift_below(IntegratorKind::Yoshida4, 2e-4);
+}
+
+#[test]
+fn higher_order_integrators_drift_less() {
+    let euler = energy_drift(IntegratorKind::SemiImplicitEuler);
+    let verlet = energy_drift(IntegratorKind::VelocityVerlet);
+    let yoshida = energy_drift(IntegratorKind::Yoshida4);
+    assert!(verlet < euler, "Verlet {:e} vs Euler {:e}", verlet, euler);
+    assert!(
+        yoshida < verlet,
+        "Yoshida {:e} vs Verlet {:e}",
+        yoshida,
+        verlet
+    );
+}
@@ -0,0 +1,116 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics::orbit::{dominant_body, periapsis_velocity, OrbitTarget, OrbitalElements};
+use n_body::physics::{self, integrator::IntegratorKind, ForceSolver};
+
+mod common;
+use common::particle;
+
+const G: f32 = 6.7;
+
+fn sun() -> Particle {
+    particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1e4)
+}
+
+#[test]
+fn circular_orbit() {
+    let sun = sun();
+    let pos = Vec2::new(0.0, 200.0);
+    let vel = physics::orbital_velocity(pos, sun.position(), sun.mass(), G);
+    // A tiny planet, so the orbit is circular about the sun
+    let planet = particle(pos, vel, 0.1, 1e-3);
+
+    let elements = OrbitalElements::new(&planet, &sun, G).unwrap();
+    assert!(elements.eccentricity < 1e-3);
+    assert!((elements.semi_major_axis - 200.0).abs() < 0.1);
+    assert!((elements.periapsis - 200.0).abs() < 0.1);
+    assert!((elements.apoapsis.unwrap() - 200.0).abs() < 0.1);
+    assert!(elements.prograde);
+}
+
+#[test]
+fn period_matches_simulation() {
+    let sun = sun();
+    let pos = Vec2::new(150.0, 0.0);
+    // Slower than circular, so the planet starts at apoapsis
+    let vel = physics::orbital_velocity(pos, sun.position(), sun.mass(), G) * 0.8;
+    let mut particles = vec![sun, particle(pos, vel, 0.1, 1e-3)];
+    let elements = OrbitalElements::new(&particles[1], &particles[0], G).unwrap();
+    assert!(elements.eccentricity > 0.2 && elements.eccentricity < 1.0);
+    assert!((elements.apoapsis.unwrap() - 150.0).abs() < 0.5);
+    // Periapsis is on the opposite side
+    assert!((elements.argument_of_periapsis.abs() - std::f32::consts::PI).abs() < 1e-2);
+
+    let period = elements.period.unwrap();
+    let steps = 2000;
+    let dt = period / steps as f32;
+    let accelerations =
+        |ps: &[Particle]| physics::accelerations(ps, G, 0.0, ForceSolver::Direct, 0.0);
+    let mut closest = f32::MAX;
+    for _ in 0..steps {
+        IntegratorKind::Yoshida4
+            .integrator()
+            .step(&mut particles, dt, &accelerations);
+        closest = closest.min(particles[1].position().distance(particles[0].position()));
+    }
+    assert!((closest - elements.periapsis).abs() / elements.periapsis < 1e-2);
+    // Back where it started after one period
+    assert!(particles[1].position().distance(pos) < 1.0);
+}
+
+#[test]
+fn escaping_body_is_unbound() {
+    let sun = sun();
+    let pos = Vec2::new(100.0, 0.0);
+    let vel = physics::orbital_velocity(pos, sun.position(), sun.mass(), G) * 2.0;
+    let elements = OrbitalElements::new(&particle(pos, -vel, 0.1, 1e-3), &sun, G).unwrap();
+    assert!(elements.eccentricity > 1.0);
+    assert!(elements.semi_major_axis < 0.0);
+    assert_eq!(elements.apoapsis, None);
+    assert_eq!(elements.period, None);
+    assert!(!elements.prograde);
+}
+
+#[test]
+fn dominant_body_is_the_strongest_pull() {
+    let particles = vec![
+        sun(),
+        particle(Vec2::new(300.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(310.0, 0.0), Vec2::ZERO, 1.0, 1.0),
+    ];
+    assert_eq!(dominant_body(&particles, 1), Some(0));
+    assert_eq!(dominant_body(&particles, 0), Some(1));
+    assert_eq!(dominant_body(&particles[..1], 0), None);
+    assert_eq!(OrbitalElements::new(&particles[0], &particles[0], G), None);
+}
+
+#[test]
+fn periapsis_velocity_gives_the_requested_orbit() {
+    let mut sun = sun();
+    sun.set_vel(Vec2::new(3.0, -2.0));
+    let pos = Vec2::new(-40.0, 90.0);
+    let mut planet = particle(pos, Vec2::ZERO, 2.0, 1.0);
+    for (eccentricity, prograde) in [(0.0, true), (0.5, false), (0.9, true)] {
+        let vel = periapsis_velocity(pos, planet.mass(), &sun, G, eccentricity, prograde);
+        planet.set_vel(vel);
+        let elements = OrbitalElements::new(&planet, &sun, G).unwrap();
+        assert!((elements.eccentricity - eccentricity).abs() < 1e-3);
+        assert!((elements.periapsis - pos.length()).abs() / pos.length() < 1e-3);
+        assert_eq!(elements.prograde, prograde);
+    }
+}
+
+#[test]
+fn orbit_targets() {
+    let particles = vec![
+        particle(Vec2::new(500.0, 0.0), Vec2::ZERO, 5.0, 1e4),
+        particle(Vec2::new(10.0, 0.0), Vec2::ZERO, 2.0, 1.0),
+        particle(Vec2::new(-50.0, 0.0), Vec2::ZERO, 3.0, 1.0),
+    ];
+    assert_eq!(OrbitTarget::Nearest.find(&particles, Vec2::ZERO), Some(1));
+    assert_eq!(
+        OrbitTarget::MostMassive.find(&particles, Vec2::ZERO),
+        Some(0)
+    );
+    assert_eq!(OrbitTarget::Nearest.find(&[], Vec2::ZERO), None);
+}
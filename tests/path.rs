@@ -0,0 +1,43 @@
+use glam::Vec2;
+use n_body::particle::path::Path;
+
+fn points(path: &Path) -> Vec<f32> {
+    path.points.iter().map(|p| p.x).collect()
+}
+
+#[test]
+fn full_path_drops_oldest_points() {
+    let mut path = Path::new(3);
+    for x in 0..5 {
+        path.add_point(Vec2::new(x as f32, 0.0));
+    }
+    assert_eq!(path.size(), 3);
+    assert_eq!(points(&path), vec![2.0, 3.0, 4.0]);
+
+    // A path that keeps nothing stays empty
+    let mut empty = Path::new(0);
+    empty.add_point(Vec2::ONE);
+    assert_eq!(empty.size(), 0);
+}
+
+#[test]
+fn capacity_can_change() {
+    let mut path = Path::new(5);
+    for x in 0..5 {
+        path.add_point(Vec2::new(x as f32, 0.0));
+    }
+    // Shrinking keeps the newest points
+    path.set_capacity(2);
+    assert_eq!(points(&path), vec![3.0, 4.0]);
+
+    // Growing keeps every point and allows more
+    path.set_capacity(4);
+    for x in 5..7 {
+        path.add_point(Vec2::new(x as f32, 0.0));
+    }
+    assert_eq!(points(&path), vec![3.0, 4.0, 5.0, 6.0]);
+
+    path.reset();
+    assert_eq!(path.size(), 0);
+    assert_eq!(path.capacity(), 4);
+}
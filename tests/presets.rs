@@ -0,0 +1,34 @@
+use n_body::physics::integrator::IntegratorKind;
+use n_body::presets::Preset;
+use n_body::simulation::{Settings, Simulation};
+
+/// Runs a preset for one period and gets the furthest any body ends up from where it started
+fn return_error(preset: Preset, g: f32, mass: f32, length: f32, steps: usize) -> f32 {
+    let mut settings = Settings::new();
+    settings.integrator = IntegratorKind::Yoshida4;
+    settings.constants.g.value = g;
+    settings.constants.softening.value = 0.0;
+    let particles = preset.particles(g, mass, length, length * 1e-3);
+    let mut sim = Simulation::new(particles.clone(), settings);
+    let period = preset.scaled_period(g, mass, length);
+    sim.run(period / steps as f32, steps);
+    particles
+        .iter()
+        .zip(&sim.particles)
+        .map(|(a, b)| a.position().distance(b.position()) / length)
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn presets_are_periodic() {
+    for preset in Preset::ALL {
+        let error = return_error(preset, 1.0, 1.0, 1.0, 100_000);
+        assert!(error < 0.05, "{:?} ended {} from its start", preset, error);
+    }
+}
+
+#[test]
+fn scaled_presets_are_periodic() {
+    let error = return_error(Preset::FigureEight, 6.7, 1.5e5, 100.0, 100_000);
+    assert!(error < 0.05, "ended {} from the start", error);
+}
@@ -0,0 +1,68 @@
+use glam::Vec2;
+use n_body::resources::constants::NumericConstants;
+use n_body::scenario::Scenario;
+
+#[test]
+fn bundled_scenarios_are_valid() {
+    let default = Scenario::from_ron(include_str!("../scenarios/default.ron")).unwrap();
+    assert_eq!(default.particles().unwrap().len(), 3);
+    let binary = Scenario::from_json(include_str!("../scenarios/binary.json")).unwrap();
+    assert_eq!(binary.bodies[0].name.as_deref(), Some("A"));
+}
+
+#[test]
+fn bodies_can_be_given_a_mass_or_a_density() {
+    let scenario = Scenario::from_ron(
+        "(bodies: [
+            (position: (1.0, 2.0), velocity: (3.0, 4.0), radius: 2.0, density: 3.0),
+            (position: (0.0, 0.0), radius: 2.0, mass: 100.0),
+        ])",
+    )
+    .unwrap();
+    let particles = scenario.particles().unwrap();
+    assert_eq!(particles[0].position(), Vec2::new(1.0, 2.0));
+    assert_eq!(particles[0].velocity(), Vec2::new(3.0, 4.0));
+    assert_eq!(particles[0].density(), 3.0);
+    assert!((particles[1].mass() - 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn invalid_bodies_name_the_body() {
+    let both = Scenario::from_json(
+        r#"{"bodies": [
+            {"position": [0, 0], "radius": 1, "mass": 1},
+            {"name": "Moon", "position": [0, 0], "radius": 1, "mass": 1, "density": 1}
+        ]}"#,
+    );
+    let message = format!("{:#}", both.unwrap_err());
+    assert!(message.contains("body 1 ('Moon')"), "{}", message);
+
+    assert!(
+        Scenario::from_json(r#"{"bodies": [{"position": [0, 0], "radius": 0, "mass": 1}]}"#)
+            .is_err()
+    );
+    assert!(Scenario::from_json(r#"{"bodies": [{"position": [0, 0], "radius": 1}]}"#).is_err());
+}
+
+#[test]
+fn malformed_files_are_errors() {
+    assert!(Scenario::from_ron(
+        "(bodies: [(position: (0.0, 0.0), radius: 1.0, mass: 1.0, spin: 2.0)])"
+    )
+    .is_err());
+    assert!(Scenario::from_json("{\"bodies\": [").is_err());
+    assert!(Scenario::load(std::path::Path::new("scenarios/solar_system.txt")).is_err());
+}
+
+#[test]
+fn constants_outside_their_range_are_not_applied() {
+    let invalid = Scenario::from_ron("(constants: (g: 1.0, restitution: 2.0), bodies: [])");
+    assert!(invalid.is_err());
+
+    let scenario = Scenario::from_ron("(constants: (g: 1.0, softening: 0.0), bodies: [])").unwrap();
+    let mut constants = NumericConstants::new();
+    scenario.constants.apply(&mut constants).unwrap();
+    assert_eq!(constants.g.value, 1.0);
+    assert_eq!(constants.softening.value, 0.0);
+    assert_eq!(constants.theta.value, NumericConstants::new().theta.value);
+}
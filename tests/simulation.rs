@@ -0,0 +1,153 @@
+use glam::Vec2;
+use n_body::particle::Particle;
+use n_body::physics::{self, diagnostics::ConservedQuantities, integrator::IntegratorKind};
+use n_body::physics::{CollisionMode, ForceSolver};
+use n_body::simulation::{Settings, Simulation};
+
+mod common;
+use common::particle;
+
+/// A sun with a few planets on circular orbits
+fn solar_system(settings: &Settings) -> Vec<Particle> {
+    let sun = particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1e4);
+    let mut particles = vec![sun.clone()];
+    for (i, r) in [150.0, 200.0, 250.0, 300.0].into_iter().enumerate() {
+        let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+        let pos = Vec2::new(r * side, 0.0);
+        let vel =
+            physics::orbital_velocity(pos, sun.position(), sun.mass(), settings.constants.g.value);
+        particles.push(particle(pos, vel, 5.0, 1.0));
+    }
+    particles
+}
+
+#[test]
+fn runs_thousands_of_steps_headless() {
+    let mut settings = Settings::new();
+    settings.integrator = IntegratorKind::Yoshida4;
+    let particles = solar_system(&settings);
+    let mut sim = Simulation::new(particles, settings);
+    let g = sim.settings.constants.g.value;
+    let eps = sim.settings.constants.softening.value;
+    let initial = ConservedQuantities::measure(&sim.particles, g, eps);
+
+    sim.run(1.0 / 120.0, 5000);
+
+    assert_eq!(sim.steps(), 5000);
+    assert!((sim.time() - 5000.0 / 120.0).abs() < 1e-3);
+    assert_eq!(sim.particles.len(), 5);
+    for p in &sim.particles {
+        assert!(p.position().is_finite() && p.velocity().is_finite());
+    }
+    let last = ConservedQuantities::measure(&sim.particles, g, eps);
+    assert!(last.energy_drift(&initial) < 1e-3);
+}
+
+#[test]
+fn force_solvers_agree() {
+    let mut direct = Settings::new();
+    direct.integrator = IntegratorKind::VelocityVerlet;
+    let mut barnes_hut = direct.clone();
+    barnes_hut.force_solver = ForceSolver::BarnesHut;
+    barnes_hut.constants.theta.value = 0.3;
+
+    let mut a = Simulation::new(solar_system(&direct), direct);
+    let mut b = Simulation::new(a.particles.clone(), barnes_hut);
+    a.run(1.0 / 120.0, 500);
+    b.run(1.0 / 120.0, 500);
+    for (a, b) in a.particles.iter().zip(&b.particles) {
+        assert!((a.position() - b.position()).length() < 1.0);
+    }
+}
+
+#[test]
+fn merging_removes_absorbed_particles() {
+    let mut settings = Settings::new();
+    settings.collision_mode = CollisionMode::Merge;
+    settings.constants.g.value = 0.0;
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(100.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(4.0, 0.0), Vec2::new(-1.0, 0.0), 2.0, 1.0),
+    ];
+    let mass: f32 = particles.iter().map(|p| p.mass()).sum();
+    let mut sim = Simulation::new(particles, settings);
+
+    let merges = sim.step(0.01);
+
+    assert_eq!(
+        merges,
+        vec![physics::Merge {
+            survivor: 0,
+            absorbed: 2
+        }]
+    );
+    assert_eq!(sim.particles.len(), 2);
+    let after: f32 = sim.particles.iter().map(|p| p.mass()).sum();
+    assert!((after - mass).abs() / mass < 1e-5);
+    // The untouched particle keeps its place in the list
+    assert_eq!(sim.particles[1].position(), Vec2::new(100.0, 0.0));
+}
+
+#[test]
+fn remove_absorbed_keeps_order() {
+    let merges = [
+        physics::Merge {
+            survivor: 0,
+            absorbed: 3,
+        },
+        physics::Merge {
+            survivor: 2,
+            absorbed: 1,
+        },
+    ];
+    let mut items = vec!['a', 'b', 'c', 'd', 'e'];
+    physics::remove_absorbed(&mut items, &merges);
+    assert_eq!(items, vec!['a', 'c', 'e']);
+}
+
+#[test]
+fn center_of_mass_matches_diagnostics() {
+    let settings = Settings::new();
+    let particles = solar_system(&settings);
+    let com = physics::center_of_mass(&particles).unwrap();
+    let measured = ConservedQuantities::measure(&particles, 6.7, 0.0).center_of_mass;
+    assert!((com.as_dvec2() - measured).length() < 1e-3);
+    assert_eq!(physics::center_of_mass(&[]), None);
+}
+
+#[test]
+fn prediction_matches_the_simulation() {
+    let mut settings = Settings::new();
+    settings.integrator = IntegratorKind::VelocityVerlet;
+    let particles = solar_system(&settings);
+    let probe = particle(Vec2::new(0.0, 120.0), Vec2::new(-450.0, 0.0), 2.0, 0.1);
+
+    let predicted = settings.predict(&particles, probe.clone(), 1.0 / 120.0, 300);
+
+    assert_eq!(predicted.len(), 301);
+    assert_eq!(predicted[0], probe.position());
+    let mut with_probe = particles.clone();
+    with_probe.push(probe);
+    let mut sim = Simulation::new(with_probe, settings);
+    for point in &predicted[1..] {
+        sim.step(1.0 / 120.0);
+        assert_eq!(*point, sim.particles[5].position());
+    }
+}
+
+#[test]
+fn prediction_stops_at_a_collision() {
+    let settings = Settings::new();
+    let particles = solar_system(&settings);
+    let before: Vec<Vec2> = particles.iter().map(|p| p.position()).collect();
+    // Falls straight into the sun
+    let probe = particle(Vec2::new(0.0, 100.0), Vec2::ZERO, 2.0, 0.1);
+
+    let predicted = settings.predict(&particles, probe, 1.0 / 120.0, 10_000);
+
+    assert!(predicted.len() < 10_000);
+    assert!(predicted.last().unwrap().length() < 10.0);
+    let after: Vec<Vec2> = particles.iter().map(|p| p.position()).collect();
+    assert_eq!(before, after);
+}
@@ -0,0 +1,246 @@
+use glam::Vec2;
+use n_body::physics::integrator::IntegratorKind;
+use n_body::physics::{self, CollisionMode, ForceSolver};
+use n_body::resources::clock::SimulationClock;
+use n_body::simulation::{Settings, Simulation};
+#[cfg(feature = "app")]
+use n_body::snapshot::ControlsState;
+use n_body::snapshot::{ClockState, ParticleState, Snapshot, VERSION};
+
+mod common;
+use common::particle;
+
+/// A sun with two planets, run for a while so every particle has a non-zero acceleration
+fn running_simulation(settings: Settings) -> Simulation {
+    let sun = particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1e4);
+    let mut particles = vec![sun.clone()];
+    for pos in [Vec2::new(150.0, 0.0), Vec2::new(-220.0, 10.0)] {
+        let vel =
+            physics::orbital_velocity(pos, sun.position(), sun.mass(), settings.constants.g.value);
+        particles.push(particle(pos, vel, 3.0, 1.0));
+    }
+    let mut sim = Simulation::new(particles, settings);
+    sim.run(1.0 / 120.0, 250);
+    sim
+}
+
+fn assert_same(a: &Simulation, b: &Simulation) {
+    assert_eq!(a.time(), b.time());
+    assert_eq!(a.steps(), b.steps());
+    assert_eq!(a.particles.len(), b.particles.len());
+    for (a, b) in a.particles.iter().zip(&b.particles) {
+        assert_eq!(a.position(), b.position());
+        assert_eq!(a.velocity(), b.velocity());
+        assert_eq!(a.acceleration(), b.acceleration());
+        assert_eq!(a.radius(), b.radius());
+        assert_eq!(a.density(), b.density());
+    }
+}
+
+#[test]
+fn restored_simulation_continues_exactly() {
+    let mut settings = Settings::new();
+    settings.integrator = IntegratorKind::Yoshida4;
+    settings.force_solver = ForceSolver::BarnesHut;
+    settings.constants.g.value = 8.0;
+    settings.constants.softening.value = 2.5;
+    let mut original = running_simulation(settings);
+
+    let snapshot = Snapshot::from_simulation(&original);
+    let from_ron = Snapshot::from_ron(&snapshot.to_ron().unwrap()).unwrap();
+    let from_json = Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+    assert_eq!(from_ron, snapshot);
+    assert_eq!(from_json, snapshot);
+
+    let mut ron = from_ron.simulation().unwrap();
+    let mut json = from_json.simulation().unwrap();
+    assert_eq!(ron.settings.integrator, IntegratorKind::Yoshida4);
+    assert_eq!(ron.settings.force_solver, ForceSolver::BarnesHut);
+    assert_eq!(ron.settings.constants.softening.value, 2.5);
+    assert_same(&original, &ron);
+
+    original.run(1.0 / 120.0, 500);
+    ron.run(1.0 / 120.0, 500);
+    json.run(1.0 / 120.0, 500);
+    assert_same(&original, &ron);
+    assert_same(&original, &json);
+}
+
+#[test]
+fn paths_round_trip() {
+    let mut path = n_body::particle::path::Path::new(3);
+    for x in 0..5 {
+        path.add_point(Vec2::new(x as f32, 0.0));
+    }
+    let state = ParticleState::with_path(0, &particle(Vec2::ONE, Vec2::ZERO, 1.0, 1.0), &path);
+    let restored = state.path();
+    assert_eq!(restored.capacity(), 3);
+    assert_eq!(restored.points, path.points);
+}
+
+#[test]
+fn merged_particles_stay_merged() {
+    let mut settings = Settings::new();
+    settings.collision_mode = CollisionMode::Merge;
+    settings.constants.g.value = 0.0;
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(4.0, 0.0), Vec2::ZERO, 2.0, 1.0),
+    ];
+    let mut sim = Simulation::new(particles, settings);
+    sim.step(0.01);
+
+    let restored = Snapshot::from_json(&Snapshot::from_simulation(&sim).to_json().unwrap())
+        .unwrap()
+        .simulation()
+        .unwrap();
+    assert_eq!(restored.settings.collision_mode, CollisionMode::Merge);
+    assert_same(&sim, &restored);
+}
+
+#[test]
+fn other_versions_are_rejected() {
+    let mut snapshot = Snapshot::from_simulation(&running_simulation(Settings::new()));
+    snapshot.version = VERSION + 1;
+    let message = format!(
+        "{:#}",
+        Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap_err()
+    );
+    assert!(message.contains("version"), "{}", message);
+
+    // Fields from a newer version are reported as a version mismatch, not an unknown field
+    let newer = format!(r#"{{"version": {}, "galaxies": []}}"#, VERSION + 1);
+    let message = format!("{:#}", Snapshot::from_json(&newer).unwrap_err());
+    assert!(message.contains("version"), "{}", message);
+}
+
+#[test]
+fn invalid_snapshots_are_errors() {
+    let valid = Snapshot::from_simulation(&running_simulation(Settings::new()));
+
+    let mut negative = valid.clone();
+    negative.particles[1].radius = -1.0;
+    let message = format!("{:#}", negative.validate().unwrap_err());
+    assert!(message.contains("particle 1"), "{}", message);
+
+    let mut unknown = valid.clone();
+    unknown.integrator = "leapfrog".to_string();
+    assert!(unknown.validate().is_err());
+
+    let mut constant = valid.clone();
+    constant.constants.restitution = Some(3.0);
+    assert!(Snapshot::from_ron(&constant.to_ron().unwrap()).is_err());
+
+    // Every field is required, so a trail isn't silently dropped
+    let mut missing: serde_json::Value = serde_json::from_str(&valid.to_json().unwrap()).unwrap();
+    missing["particles"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("path_capacity");
+    assert!(Snapshot::from_json(&missing.to_string()).is_err());
+
+    assert!(Snapshot::from_json("{\"version\": 1, \"particles\": [").is_err());
+    assert!(Snapshot::load(std::path::Path::new("scenarios/solar_system.txt")).is_err());
+}
+
+#[test]
+fn clock_settings_round_trip() {
+    let mut clock = SimulationClock::new();
+    clock.dt = 0.02;
+    clock.substeps = 3;
+    clock.time_scale = 0.25;
+    clock.max_steps_per_frame = 32;
+    clock.toggle_pause();
+
+    let mut snapshot = Snapshot::from_simulation(&running_simulation(Settings::new()));
+    snapshot.clock = Some(ClockState::new(&clock));
+    let restored = Snapshot::from_ron(&snapshot.to_ron().unwrap()).unwrap();
+    let mut loaded = SimulationClock::new();
+    restored.clock.unwrap().apply(&mut loaded);
+    assert_eq!(loaded.dt, 0.02);
+    assert_eq!(loaded.substeps, 3);
+    assert_eq!(loaded.time_scale, 0.25);
+    assert_eq!(loaded.max_steps_per_frame, 32);
+    assert!(loaded.paused);
+
+    let mut invalid = snapshot;
+    invalid.clock = Some(ClockState {
+        time_scale: -1.0,
+        ..ClockState::new(&clock)
+    });
+    assert!(invalid.validate().is_err());
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn controls_round_trip() {
+    use n_body::colormap::{ColorMode, Colormap};
+    use n_body::resources::controls::Controls;
+
+    let controls = Controls {
+        show_path: true,
+        trail_length: 750,
+        show_velocity: true,
+        show_acceleration: true,
+        velocity_scale: 2.0,
+        acceleration_scale: 0.02,
+        particle_color: bevy::prelude::Color::rgba(0.5, 0.25, 1.0, 1.0),
+        color_mode: ColorMode::KineticEnergy,
+        colormap: Colormap::Magma,
+        ..Default::default()
+    };
+
+    let mut snapshot = Snapshot::from_simulation(&running_simulation(Settings::new()));
+    snapshot.controls = Some(ControlsState::new(&controls));
+    let restored = Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+    let mut loaded = Controls::default();
+    restored.controls.unwrap().apply(&mut loaded).unwrap();
+    assert!(loaded.show_path);
+    assert_eq!(loaded.trail_length, 750);
+    assert!(loaded.show_velocity);
+    assert!(loaded.show_acceleration);
+    assert_eq!(loaded.velocity_scale, 2.0);
+    assert_eq!(loaded.acceleration_scale, 0.02);
+    assert_eq!(loaded.particle_color, controls.particle_color);
+    assert_eq!(loaded.particle_stroke, controls.particle_stroke);
+    assert_eq!(loaded.color_mode, ColorMode::KineticEnergy);
+    assert_eq!(loaded.colormap, Colormap::Magma);
+
+    let mut invalid = snapshot;
+    invalid.controls.as_mut().unwrap().color_mode = "rainbow".to_string();
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn particle_ids_are_restored_and_not_reused() {
+    let mut settings = Settings::new();
+    settings.collision_mode = CollisionMode::Merge;
+    settings.constants.g.value = 0.0;
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::ZERO, 2.0, 1.0),
+        particle(Vec2::new(100.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(104.0, 0.0), Vec2::ZERO, 2.0, 1.0),
+    ];
+    let mut sim = Simulation::new(particles, settings);
+    sim.step(0.01);
+    assert_eq!(sim.ids(), &[0, 1]);
+
+    let mut restored = Snapshot::from_ron(&Snapshot::from_simulation(&sim).to_ron().unwrap())
+        .unwrap()
+        .simulation()
+        .unwrap();
+    assert_eq!(restored.ids(), sim.ids());
+    // The absorbed particle's ID stays retired
+    let id = restored.add(particle(Vec2::new(0.0, 50.0), Vec2::ZERO, 1.0, 1.0));
+    assert_eq!(id, 3);
+}
+
+#[test]
+fn duplicate_particle_ids_are_given_new_ones() {
+    let mut snapshot = Snapshot::from_simulation(&running_simulation(Settings::new()));
+    snapshot.next_id = 0;
+    snapshot.particles[0].id = 7;
+    snapshot.particles[1].id = 7;
+    snapshot.particles[2].id = 2;
+    assert_eq!(snapshot.ids(), (vec![7, 8, 2], 9));
+}
@@ -0,0 +1,73 @@
+use glam::Vec2;
+use n_body::physics::{self, diagnostics, ForceSolver};
+
+mod common;
+use common::particle;
+
+const G: f32 = 6.7;
+
+#[test]
+fn coincident_particles_have_finite_forces() {
+    let particles = vec![
+        particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0),
+    ];
+    for solver in ForceSolver::ALL {
+        for softening in [0.0, 1.0] {
+            for acc in physics::accelerations(&particles, G, softening, solver, 0.5) {
+                assert!(acc.is_finite(), "{:?} with ε = {}", solver, softening);
+                assert_eq!(acc, Vec2::ZERO);
+            }
+        }
+    }
+}
+
+#[test]
+fn coincident_particles_have_finite_potential_energy() {
+    let (a, b) = (
+        particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0),
+    );
+    assert!(diagnostics::potential_energy(&a, &b, G, 0.0).is_finite());
+    let softened = diagnostics::potential_energy(&a, &b, G, 2.0);
+    assert!(softened.is_finite());
+    assert_eq!(softened, -(G * a.mass() * b.mass()) / 2.0);
+}
+
+#[test]
+fn coincident_particles_are_separated_and_bounced() {
+    let mut a = particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0);
+    let mut b = particle(Vec2::ONE, Vec2::ZERO, 5.0, 1.0);
+    b.set_vel(Vec2::new(-1.0, 0.0));
+    physics::resolve_intersection(&mut a, &mut b);
+    physics::resolve_collision(&mut a, &mut b, 0.8);
+    assert!(a.position().is_finite() && b.position().is_finite());
+    assert!(a.velocity().is_finite() && b.velocity().is_finite());
+    assert_ne!(a.position(), b.position());
+}
+
+#[test]
+fn softening_limits_close_range_force() {
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(0.01, 0.0), Vec2::ZERO, 5.0, 1.0),
+    ];
+    let hard = physics::accelerations(&particles, G, 0.0, ForceSolver::Direct, 0.0);
+    let soft = physics::accelerations(&particles, G, 1.0, ForceSolver::Direct, 0.0);
+    assert!(soft[0].length() < hard[0].length());
+    // The softened force peaks at r = ε / sqrt(2), it can never exceed that value
+    let peak = 2.0 * G * particles[1].mass() / (3.0 * 3.0f32.sqrt());
+    assert!(soft[0].length() <= peak);
+}
+
+#[test]
+fn softening_is_negligible_at_long_range() {
+    let particles = vec![
+        particle(Vec2::ZERO, Vec2::ZERO, 5.0, 1.0),
+        particle(Vec2::new(1000.0, 0.0), Vec2::ZERO, 5.0, 1.0),
+    ];
+    let hard = physics::accelerations(&particles, G, 0.0, ForceSolver::Direct, 0.0);
+    let soft = physics::accelerations(&particles, G, 1.0, ForceSolver::Direct, 0.0);
+    let relative = (hard[0] - soft[0]).length() / hard[0].length();
+    assert!(relative < 1e-5, "relative difference {}", relative);
+}